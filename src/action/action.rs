@@ -13,3 +13,38 @@ pub struct Action {
     pub payload: Option<Vec<Line>>,
     pub kind: ActionType,
 }
+
+impl Action {
+    // Where a point elsewhere in the buffer ends up once this action is applied
+    pub fn transform(&self, point: Point) -> Point {
+        let start = self.start;
+        match self.kind {
+            ActionType::Insert => {
+                let payload = self.payload.as_deref().unwrap_or_default();
+                let added = payload.len().saturating_sub(1);
+                if point < start {
+                    point
+                } else if point.y > start.y {
+                    Point::new(point.x, point.y + added)
+                } else if added == 0 {
+                    Point::new(point.x + payload.first().map_or(0, Line::len), point.y)
+                } else {
+                    let last = payload.last().map_or(0, Line::len);
+                    Point::new(last + point.x - start.x, point.y + added)
+                }
+            }
+            ActionType::Remove => {
+                let end = self.end.unwrap_or(start);
+                if point <= start {
+                    point
+                } else if point <= end {
+                    start
+                } else if point.y == end.y {
+                    Point::new(start.x + point.x - end.x, start.y)
+                } else {
+                    Point::new(point.x, point.y - (end.y - start.y))
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
+#[allow(clippy::module_inception)]
 pub mod action;
 pub mod undo_stack;
 
 pub use action::{Action, ActionType};
-pub use undo_stack::{UndoStack, UndoNode};
+pub use undo_stack::{UndoNode, UndoStack};
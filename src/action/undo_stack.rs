@@ -1,34 +1,88 @@
 use super::Action;
 
+// A single undo step, made of one or more actions applied in order
 pub struct UndoNode {
-    pub redo: Action,
-    pub undo: Action,
+    pub redo: Vec<Action>,
+    pub undo: Vec<Action>,
 }
 
+#[derive(Default)]
 pub struct UndoStack {
     pub nodes: Vec<UndoNode>,
     // 1-based index
     pub index: usize,
+    // Actions added while a group is open are collected into one step.
+    // Groups can nest, only the outermost one is kept.
+    pub group: Option<UndoNode>,
+    pub group_depth: usize,
+    // Index at the last save, and whether the steps leading to it were
+    // since dropped by editing after an undo
+    pub saved_index: usize,
+    pub saved_lost: bool,
 }
 
 impl UndoStack {
     pub fn new() -> Self {
-        Self {
-            nodes: vec![],
-            index: 0,
-        }
+        Self::default()
     }
 
     pub fn add(&mut self, redo: Action, undo: Action) {
+        self.add_group(vec![redo], vec![undo]);
+    }
+
+    // Add several actions as one step. `undo` is applied in the given order,
+    // so it's usually `redo`'s inverses in reverse.
+    pub fn add_group(&mut self, redo: Vec<Action>, undo: Vec<Action>) {
+        if redo.is_empty() {
+            return;
+        }
+        if let Some(group) = &mut self.group {
+            group.redo.extend(redo);
+            // Later actions have to be undone first
+            group.undo.splice(0..0, undo);
+            return;
+        }
         // Remove all nodes past current index if they exist
         if self.index < self.nodes.len() {
             self.nodes.truncate(self.index);
+            if self.saved_index > self.index {
+                self.saved_lost = true;
+            }
         }
         self.nodes.push(UndoNode { redo, undo });
         self.index += 1;
     }
 
-    pub fn undo(&mut self) -> Option<Action> {
+    pub fn begin_group(&mut self) {
+        if self.group_depth == 0 {
+            self.group = Some(UndoNode {
+                redo: vec![],
+                undo: vec![],
+            });
+        }
+        self.group_depth += 1;
+    }
+
+    pub fn end_group(&mut self) {
+        self.group_depth = self.group_depth.saturating_sub(1);
+        if self.group_depth == 0
+            && let Some(group) = self.group.take()
+        {
+            self.add_group(group.redo, group.undo);
+        }
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved_index = self.index;
+        self.saved_lost = false;
+    }
+
+    // Whether the buffer differs from what was last saved
+    pub fn is_modified(&self) -> bool {
+        self.saved_lost || self.index != self.saved_index || self.group.is_some()
+    }
+
+    pub fn undo(&mut self) -> Option<Vec<Action>> {
         if self.index == 0 {
             return None;
         }
@@ -37,7 +91,7 @@ impl UndoStack {
         Some(self.nodes[self.index].undo.clone())
     }
 
-    pub fn redo(&mut self) -> Option<Action> {
+    pub fn redo(&mut self) -> Option<Vec<Action>> {
         if self.index == self.nodes.len() {
             return None;
         }
@@ -0,0 +1,51 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Split a command line like "xclip -selection clipboard" into its parts
+fn parse(command: &str) -> Result<Command, String> {
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| "empty clipboard command".to_string())?;
+    let mut command = Command::new(program);
+    command.args(parts);
+    Ok(command)
+}
+
+// Pipe `text` into a command such as `wl-copy` or `xclip`
+pub fn copy_with_command(command: &str, text: &str) -> Result<(), String> {
+    let mut child = parse(command)?
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("{}: {}", command, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("{}: {}", command, e))?;
+    }
+
+    let status = child.wait().map_err(|e| format!("{}: {}", command, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", command, status))
+    }
+}
+
+// Read the clipboard from a command such as `wl-paste` or `xclip -o`
+pub fn paste_with_command(command: &str) -> Result<String, String> {
+    let output = parse(command)?
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| format!("{}: {}", command, e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!("{} exited with {}", command, output.status))
+    }
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Recently copied and cut text, newest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClipboardHistory {
+    pub entries: Vec<String>,
+    #[serde(skip)]
+    pub capacity: usize,
+}
+
+impl ClipboardHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: vec![],
+            capacity,
+        }
+    }
+
+    // Add text as the newest entry. Copying the same text again moves it to
+    // the front instead of keeping a duplicate.
+    pub fn push(&mut self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+        self.entries.retain(|entry| *entry != text);
+        self.entries.insert(0, text);
+        self.entries.truncate(self.capacity);
+    }
+
+    pub fn latest(&self) -> Option<&String> {
+        self.entries.first()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Read a history saved by `save`. A missing file gives an empty history.
+    pub fn load(path: &Path, capacity: usize) -> Result<Self, String> {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new(capacity)),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+
+        let mut history: ClipboardHistory =
+            toml::from_str(&source).map_err(|e| format!("{}: {}", path.display(), e))?;
+        history.capacity = capacity;
+        history.entries.truncate(capacity);
+        Ok(history)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let source = toml::to_string(self).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        fs::write(path, source).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
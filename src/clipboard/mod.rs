@@ -0,0 +1,7 @@
+pub mod command;
+pub mod history;
+pub mod osc52;
+
+pub use command::{copy_with_command, paste_with_command};
+pub use history::ClipboardHistory;
+pub use osc52::copy_with_osc52;
@@ -0,0 +1,37 @@
+use std::io::{self, Write, stdout};
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Terminals drop or truncate longer sequences
+const MAX_ENCODED_LEN: usize = 100_000;
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+// Ask the terminal to put `text` on the system clipboard. This works over
+// SSH, but the terminal has to support and allow it.
+pub fn copy_with_osc52(text: &str) -> io::Result<()> {
+    let encoded = base64(text.as_bytes());
+    if encoded.len() > MAX_ENCODED_LEN {
+        return Err(io::Error::other("selection too large for OSC 52"));
+    }
+
+    let mut stdout = stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", encoded)?;
+    stdout.flush()
+}
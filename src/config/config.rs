@@ -0,0 +1,168 @@
+use super::settings::{
+    BufferSettings, ClipboardSettings, FiletypeSettings, validate_pairs, validate_tab_width,
+};
+use crate::keymap::Keymap;
+use crate::syntax::Language;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub theme: String,
+    // Seconds a status message stays visible
+    pub status_timeout: f64,
+    pub status_bar: bool,
+    // Lines, or columns with Shift, scrolled per mouse wheel notch
+    pub scroll_lines: usize,
+    pub editor: BufferSettings,
+    pub filetype: HashMap<String, FiletypeSettings>,
+    // Key sequences mapped to command names, on top of the default keymap
+    pub keys: HashMap<String, String>,
+    pub clipboard: ClipboardSettings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: "default".to_string(),
+            status_timeout: 3.0,
+            status_bar: true,
+            scroll_lines: 3,
+            editor: BufferSettings::default(),
+            filetype: HashMap::new(),
+            keys: HashMap::new(),
+            clipboard: ClipboardSettings::default(),
+        }
+    }
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("fedit"))
+}
+
+// Where state such as the clipboard history is kept between sessions
+pub fn data_dir() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .map(|dir| dir.join("fedit"))
+}
+
+impl Config {
+    // Load the config from `path`, or from the default location if none is
+    // given. A missing default config file is not an error.
+    pub fn load(path: Option<&Path>) -> Result<Config, String> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match config_dir() {
+                Some(dir) => (dir.join("config.toml"), false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(_) if !required && !path.exists() => return Ok(Config::default()),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+
+        let config: Config =
+            toml::from_str(&source).map_err(|e| format!("{}: {}", path.display(), e))?;
+        config
+            .validate()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.status_timeout.is_finite() || self.status_timeout < 0.0 {
+            return Err(format!(
+                "status_timeout must be a non-negative number of seconds, got {}",
+                self.status_timeout
+            ));
+        }
+
+        if self.scroll_lines == 0 {
+            return Err("scroll_lines must be at least 1".to_string());
+        }
+
+        Keymap::with_overrides(&self.keys).map_err(|e| format!("[keys] {}", e))?;
+
+        validate_tab_width(self.editor.tab_width).map_err(|e| format!("[editor] {}", e))?;
+        if let Some(indent_size) = self.editor.indent_size {
+            validate_tab_width(indent_size).map_err(|e| format!("[editor] {}", e))?;
+        }
+        validate_pairs(&self.editor.pairs).map_err(|e| format!("[editor] {}", e))?;
+
+        for (name, overrides) in &self.filetype {
+            if !Language::ALL.iter().any(|language| language.name() == name) {
+                let known: Vec<&str> = Language::ALL.iter().map(|l| l.name()).collect();
+                return Err(format!(
+                    "unknown filetype '{}', expected one of: {}",
+                    name,
+                    known.join(", ")
+                ));
+            }
+            for width in [overrides.tab_width, overrides.indent_size]
+                .into_iter()
+                .flatten()
+            {
+                validate_tab_width(width).map_err(|e| format!("[filetype.{}] {}", name, e))?;
+            }
+            validate_pairs(&overrides.pairs).map_err(|e| format!("[filetype.{}] {}", name, e))?;
+        }
+
+        for command in [&self.clipboard.copy_command, &self.clipboard.paste_command]
+            .into_iter()
+            .flatten()
+        {
+            if command.trim().is_empty() {
+                return Err("[clipboard] commands can't be empty".to_string());
+            }
+        }
+        if self.clipboard.history_size == 0 {
+            return Err("[clipboard] history_size must be at least 1".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn status_timeout(&self) -> Duration {
+        Duration::from_secs_f64(self.status_timeout)
+    }
+
+    pub fn status_bar_height(&self) -> usize {
+        if self.status_bar { 1 } else { 0 }
+    }
+
+    // Settings for a buffer of the given language, with overrides applied
+    pub fn buffer_settings(&self, language: Language) -> BufferSettings {
+        let mut settings = self.editor.clone();
+        if settings.indent_after.is_none() {
+            settings.indent_after = Some(language.indent_after().to_string());
+        }
+        if settings.pairs.is_none() {
+            settings.pairs = Some(language.pairs().to_string());
+        }
+        if settings.line_comment.is_none() {
+            settings.line_comment = Some(language.line_comment().to_string());
+        }
+        if settings.block_comment.is_none() {
+            let (open, close) = language.block_comment();
+            settings.block_comment = Some((open.to_string(), close.to_string()));
+        }
+        if let Some(overrides) = self.filetype.get(language.name()) {
+            settings.merge(overrides);
+        }
+        settings
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Numeric ranges like {1..100} are expanded, refuse to expand huge ones
+const MAX_RANGE_EXPANSION: u64 = 1000;
+// Patterns a whole section header may expand to before it's matched literally
+const MAX_PATTERNS: usize = 1000;
+
+struct Section {
+    patterns: Vec<String>,
+    properties: Vec<(String, String)>,
+}
+
+struct EditorConfigFile {
+    directory: PathBuf,
+    root: bool,
+    sections: Vec<Section>,
+}
+
+// Properties from all `.editorconfig` files that apply to `path`, with the
+// closest file taking precedence. Values are lowercased and `unset` removes
+// a property.
+pub fn properties_for(path: &Path) -> HashMap<String, String> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir().unwrap_or_default().join(path)
+    };
+
+    let mut files = vec![];
+    for directory in path.ancestors().skip(1) {
+        if let Ok(source) = fs::read_to_string(directory.join(".editorconfig")) {
+            let file = parse(&source, directory);
+            let root = file.root;
+            files.push(file);
+            if root {
+                break;
+            }
+        }
+    }
+
+    let mut properties = HashMap::new();
+    for file in files.iter().rev() {
+        let Ok(relative) = path.strip_prefix(&file.directory) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+
+        for section in &file.sections {
+            if section
+                .patterns
+                .iter()
+                .any(|p| section_matches(p, &relative))
+            {
+                for (key, value) in &section.properties {
+                    if value == "unset" {
+                        properties.remove(key);
+                    } else {
+                        properties.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    properties
+}
+
+fn parse(source: &str, directory: &Path) -> EditorConfigFile {
+    let mut file = EditorConfigFile {
+        directory: directory.to_path_buf(),
+        root: false,
+        sections: vec![],
+    };
+
+    for line in source.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            file.sections.push(Section {
+                patterns: expand_braces(glob),
+                properties: vec![],
+            });
+        } else if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+
+            match file.sections.last_mut() {
+                Some(section) => section.properties.push((key, value)),
+                // Only `root` is allowed before the first section
+                None => file.root = key == "root" && value == "true",
+            }
+        }
+    }
+
+    file
+}
+
+// Globs without a slash match the file name in any directory, others are
+// relative to the directory of the .editorconfig file
+fn section_matches(pattern: &str, relative: &str) -> bool {
+    let (pattern, subject) = if pattern.contains('/') {
+        (pattern.strip_prefix('/').unwrap_or(pattern), relative)
+    } else {
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        (pattern, name)
+    };
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let subject: Vec<char> = subject.chars().collect();
+    glob_matches(&pattern, &subject)
+}
+
+// Match `*`, `**`, `?` and `[...]` character classes
+fn glob_matches(pattern: &[char], path: &[char]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            (0..=path.len()).any(|i| glob_matches(rest, &path[i..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            let segment = path.iter().position(|c| *c == '/').unwrap_or(path.len());
+            (0..=segment).any(|i| glob_matches(rest, &path[i..]))
+        }
+        Some('?') => {
+            path.first().is_some_and(|c| *c != '/') && glob_matches(&pattern[1..], &path[1..])
+        }
+        Some('[') => match pattern.iter().position(|c| *c == ']') {
+            Some(end) if end > 1 => {
+                let Some(c) = path.first() else {
+                    return false;
+                };
+                let class = &pattern[1..end];
+                let (negated, class) = match class.first() {
+                    Some('!') | Some('^') => (true, &class[1..]),
+                    _ => (false, class),
+                };
+                class_contains(class, *c) != negated
+                    && *c != '/'
+                    && glob_matches(&pattern[end + 1..], &path[1..])
+            }
+            _ => path.first() == Some(&'[') && glob_matches(&pattern[1..], &path[1..]),
+        },
+        Some('\\') if pattern.len() > 1 => {
+            path.first() == Some(&pattern[1]) && glob_matches(&pattern[2..], &path[1..])
+        }
+        Some(c) => path.first() == Some(c) && glob_matches(&pattern[1..], &path[1..]),
+    }
+}
+
+fn class_contains(class: &[char], c: char) -> bool {
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            if (class[i]..=class[i + 2]).contains(&c) {
+                return true;
+            }
+            i += 3;
+        } else {
+            if class[i] == c {
+                return true;
+            }
+            i += 1;
+        }
+    }
+    false
+}
+
+// Expand `{a,b}` alternatives and `{1..3}` ranges into separate patterns
+fn expand_braces(pattern: &str) -> Vec<String> {
+    expand_braces_up_to(pattern, MAX_PATTERNS).unwrap_or_else(|| vec![pattern.to_string()])
+}
+
+// Expand into at most `limit` patterns, or None if there would be more
+fn expand_braces_up_to(pattern: &str, limit: usize) -> Option<Vec<String>> {
+    if limit == 0 {
+        return None;
+    }
+    let Some(open) = pattern.find('{') else {
+        return Some(vec![pattern.to_string()]);
+    };
+
+    // Find the matching close brace
+    let mut depth = 0;
+    let mut close = None;
+    for (i, c) in pattern[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            _ => (),
+        }
+    }
+    let Some(close) = close else {
+        return Some(vec![pattern.to_string()]);
+    };
+
+    let prefix = &pattern[..open];
+    let inner = &pattern[open + 1..close];
+    let suffix = &pattern[close + 1..];
+
+    let alternatives: Vec<String> = if let Some((a, b)) = inner.split_once("..")
+        && let (Ok(a), Ok(b)) = (a.parse::<i64>(), b.parse::<i64>())
+        && a.abs_diff(b) <= MAX_RANGE_EXPANSION
+    {
+        (a.min(b)..=a.max(b)).map(|n| n.to_string()).collect()
+    } else {
+        split_alternatives(inner)
+    };
+
+    let mut patterns = vec![];
+    for alternative in alternatives {
+        let pattern = format!("{}{}{}", prefix, alternative, suffix);
+        patterns.extend(expand_braces_up_to(&pattern, limit - patterns.len())?);
+    }
+    Some(patterns)
+}
+
+// Split on commas that aren't inside nested braces
+fn split_alternatives(inner: &str) -> Vec<String> {
+    let mut alternatives = vec![];
+    let mut depth = 0;
+    let mut current = String::new();
+
+    for c in inner.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                alternatives.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => (),
+        }
+        current.push(c);
+    }
+    alternatives.push(current);
+
+    alternatives
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_braces_caps_total_patterns() {
+        assert_eq!(expand_braces("*.{rs,toml}"), ["*.rs", "*.toml"]);
+        assert_eq!(expand_braces("{1..3}").len(), 3);
+
+        // Each range is small, but together they'd expand to a billion
+        let pattern = "{1..999}{1..999}{1..999}";
+        assert_eq!(expand_braces(pattern), [pattern]);
+        let pattern = "{a,b}".repeat(20);
+        assert_eq!(expand_braces(&pattern), [pattern]);
+    }
+}
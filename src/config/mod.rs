@@ -0,0 +1,7 @@
+#[allow(clippy::module_inception)]
+pub mod config;
+pub mod editorconfig;
+pub mod settings;
+
+pub use config::{Config, config_dir, data_dir};
+pub use settings::{BufferSettings, ClipboardSettings, FiletypeSettings};
@@ -0,0 +1,170 @@
+use serde::Deserialize;
+
+// Settings that apply to a single buffer and can be overridden per filetype
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BufferSettings {
+    pub tab_width: usize,
+    // Width of one indentation level, defaults to the tab width
+    pub indent_size: Option<usize>,
+    pub insert_spaces: bool,
+    pub trim_trailing_whitespace: bool,
+    // Keep the indentation of the previous line on Enter
+    pub auto_indent: bool,
+    // Characters that add an indentation level when a line ends with them,
+    // the filetype's defaults if not set
+    pub indent_after: Option<String>,
+    // Opening and closing characters inserted together, written one pair
+    // after another like "()[]". The filetype's defaults if not set.
+    pub pairs: Option<String>,
+    // Comment syntax, the filetype's defaults if not set. Empty strings
+    // mean the filetype has no such comments.
+    pub line_comment: Option<String>,
+    pub block_comment: Option<(String, String)>,
+    pub show_whitespace: bool,
+    pub indent_guides: bool,
+}
+
+impl Default for BufferSettings {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            indent_size: None,
+            insert_spaces: false,
+            trim_trailing_whitespace: false,
+            auto_indent: true,
+            indent_after: None,
+            pairs: None,
+            line_comment: None,
+            block_comment: None,
+            show_whitespace: false,
+            indent_guides: false,
+        }
+    }
+}
+
+// A `[filetype.<name>]` table, every field is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FiletypeSettings {
+    pub tab_width: Option<usize>,
+    pub indent_size: Option<usize>,
+    pub insert_spaces: Option<bool>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub auto_indent: Option<bool>,
+    pub indent_after: Option<String>,
+    pub pairs: Option<String>,
+    pub line_comment: Option<String>,
+    pub block_comment: Option<(String, String)>,
+    pub show_whitespace: Option<bool>,
+    pub indent_guides: Option<bool>,
+}
+
+impl BufferSettings {
+    pub fn indent_width(&self) -> usize {
+        self.indent_size.unwrap_or(self.tab_width)
+    }
+
+    // The auto-closing pairs as (open, close)
+    pub fn pairs(&self) -> Vec<(char, char)> {
+        let chars: Vec<char> = self.pairs.as_deref().unwrap_or_default().chars().collect();
+        chars
+            .chunks_exact(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect()
+    }
+
+    // The whitespace for one level of indentation
+    pub fn indent_unit(&self) -> Vec<String> {
+        if self.insert_spaces {
+            vec![" ".to_string(); self.indent_width()]
+        } else {
+            vec!["\t".to_string()]
+        }
+    }
+
+    pub fn merge(&mut self, overrides: &FiletypeSettings) {
+        if let Some(tab_width) = overrides.tab_width {
+            self.tab_width = tab_width;
+        }
+        if let Some(indent_size) = overrides.indent_size {
+            self.indent_size = Some(indent_size);
+        }
+        if let Some(insert_spaces) = overrides.insert_spaces {
+            self.insert_spaces = insert_spaces;
+        }
+        if let Some(trim_trailing_whitespace) = overrides.trim_trailing_whitespace {
+            self.trim_trailing_whitespace = trim_trailing_whitespace;
+        }
+        if let Some(auto_indent) = overrides.auto_indent {
+            self.auto_indent = auto_indent;
+        }
+        if let Some(indent_after) = &overrides.indent_after {
+            self.indent_after = Some(indent_after.clone());
+        }
+        if let Some(pairs) = &overrides.pairs {
+            self.pairs = Some(pairs.clone());
+        }
+        if let Some(line_comment) = &overrides.line_comment {
+            self.line_comment = Some(line_comment.clone());
+        }
+        if let Some(block_comment) = &overrides.block_comment {
+            self.block_comment = Some(block_comment.clone());
+        }
+        if let Some(show_whitespace) = overrides.show_whitespace {
+            self.show_whitespace = show_whitespace;
+        }
+        if let Some(indent_guides) = overrides.indent_guides {
+            self.indent_guides = indent_guides;
+        }
+    }
+}
+
+// The `[clipboard]` table
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClipboardSettings {
+    // Set the system clipboard through the terminal with OSC 52
+    pub osc52: bool,
+    // Commands that copy from stdin and paste to stdout, like "wl-copy"
+    // and "wl-paste"
+    pub copy_command: Option<String>,
+    pub paste_command: Option<String>,
+    // Number of copied snippets kept for the history picker and cycling
+    pub history_size: usize,
+    // Keep the history across sessions
+    pub persist_history: bool,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self {
+            osc52: true,
+            copy_command: None,
+            paste_command: None,
+            history_size: 20,
+            persist_history: false,
+        }
+    }
+}
+
+pub fn validate_pairs(pairs: &Option<String>) -> Result<(), String> {
+    match pairs {
+        Some(pairs) if pairs.chars().count() % 2 != 0 => Err(format!(
+            "pairs must list opening and closing characters in pairs, got \"{}\"",
+            pairs
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_tab_width(tab_width: usize) -> Result<(), String> {
+    if (1..=16).contains(&tab_width) {
+        Ok(())
+    } else {
+        Err(format!(
+            "tab width must be between 1 and 16, got {}",
+            tab_width
+        ))
+    }
+}
@@ -0,0 +1,66 @@
+use crate::editor::core::{Editor, Motion};
+use crate::types::{Cursor, Point};
+
+impl Editor {
+    // Select a rectangle of display columns between two corners, given as
+    // (column, line). Each line gets a cursor with its own selection.
+    pub fn set_block(&mut self, anchor: Point, head: Point) {
+        let tab_width = self.settings.tab_width;
+        let (left, right) = (anchor.x.min(head.x), anchor.x.max(head.x));
+        let (top, bottom) = (anchor.y.min(head.y), anchor.y.max(head.y));
+
+        let mut cursors: Vec<Cursor> = (top..=bottom)
+            .map(|y| {
+                let line = &self.content[y];
+                let start = line.x_at_width(left, tab_width).unwrap_or(line.len());
+                // Graphemes only partly inside the block, like tabs, count
+                let end = match right > left {
+                    true => line
+                        .x_at_width(right - 1, tab_width)
+                        .map_or(line.len(), |x| x + 1),
+                    false => start,
+                };
+                let (from, to) = if head.x >= anchor.x {
+                    (start, end)
+                } else {
+                    (end, start)
+                };
+
+                Cursor {
+                    position: Point::new(to, y),
+                    selection: (start != end).then_some([Point::new(from, y), Point::new(to, y)]),
+                    preferred_width: head.x,
+                }
+            })
+            .collect();
+
+        let primary = cursors.remove(head.y - top);
+        self.cursor = primary.position;
+        self.selection = primary.selection;
+        self.preferred_width = primary.preferred_width;
+        self.extra_cursors = cursors;
+        self.block = Some([anchor, head]);
+        self.adjust_offset();
+    }
+
+    // Grow or shrink the block selection, starting one at the cursor
+    pub fn extend_block(&mut self, motion: Motion) {
+        let [anchor, mut head] = self.block.unwrap_or_else(|| {
+            let width = self
+                .get_current_line()
+                .width_to(self.cursor.x, self.settings.tab_width);
+            let corner = Point::new(width, self.cursor.y);
+            [corner, corner]
+        });
+
+        match motion {
+            Motion::Up => head.y = head.y.saturating_sub(1),
+            Motion::Down => head.y = (head.y + 1).min(self.content.len() - 1),
+            Motion::Left => head.x = head.x.saturating_sub(1),
+            Motion::Right => head.x += 1,
+            _ => return,
+        }
+
+        self.set_block(anchor, head);
+    }
+}
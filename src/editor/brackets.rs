@@ -0,0 +1,168 @@
+use crate::editor::core::Editor;
+use crate::syntax::TokenKind;
+use crate::types::Point;
+
+const BRACKETS: [(&str, &str); 3] = [("(", ")"), ("[", "]"), ("{", "}")];
+
+// How far to look for a match, so huge files don't stall every redraw
+const MAX_SCAN_LINES: usize = 10_000;
+
+fn is_open(grapheme: &str) -> bool {
+    BRACKETS.iter().any(|(open, _)| *open == grapheme)
+}
+
+fn is_close(grapheme: &str) -> bool {
+    BRACKETS.iter().any(|(_, close)| *close == grapheme)
+}
+
+fn is_pair(open: &str, close: &str) -> bool {
+    BRACKETS.contains(&(open, close))
+}
+
+impl Editor {
+    // Brackets in code, strings and comments only match others in the same
+    // kind of text
+    fn context(&mut self, point: Point) -> Option<TokenKind> {
+        self.highlighter.update(&self.content, point.y + 1);
+        match self.highlighter.tokens(point.y).get(point.x) {
+            Some(TokenKind::String | TokenKind::Escape) => Some(TokenKind::String),
+            Some(TokenKind::Comment) => Some(TokenKind::Comment),
+            _ => None,
+        }
+    }
+
+    // Find the first bracket after (or before) `from` that isn't balanced by
+    // one in between, skipping brackets in a different kind of text
+    fn find_unbalanced(
+        &mut self,
+        from: Point,
+        forward: bool,
+        context: Option<TokenKind>,
+    ) -> Option<Point> {
+        let mut depth = 0;
+        let (first, last) = if forward {
+            (
+                from.y,
+                (from.y + MAX_SCAN_LINES).min(self.content.len() - 1),
+            )
+        } else {
+            (from.y.saturating_sub(MAX_SCAN_LINES), from.y)
+        };
+
+        let mut y = from.y;
+        loop {
+            let len = self.content[y].len();
+            let xs: Box<dyn Iterator<Item = usize>> = match (forward, y == from.y) {
+                (true, true) => Box::new(from.x + 1..len),
+                (true, false) => Box::new(0..len),
+                (false, true) => Box::new((0..from.x.min(len)).rev()),
+                (false, false) => Box::new((0..len).rev()),
+            };
+
+            for x in xs {
+                let grapheme = self.content[y].graphemes[x].as_str();
+                let (opens, closes) = (is_open(grapheme), is_close(grapheme));
+                if !(opens || closes) || self.context(Point::new(x, y)) != context {
+                    continue;
+                }
+
+                if opens == forward {
+                    depth += 1;
+                } else if depth == 0 {
+                    return Some(Point::new(x, y));
+                } else {
+                    depth -= 1;
+                }
+            }
+
+            if forward && y < last {
+                y += 1;
+            } else if !forward && y > first {
+                y -= 1;
+            } else {
+                return None;
+            }
+        }
+    }
+
+    // The bracket under the cursor, or just before it
+    fn bracket_near_cursor(&self) -> Option<Point> {
+        let line = self.get_current_line();
+        let x = self.cursor.x;
+        [Some(x), x.checked_sub(1)]
+            .into_iter()
+            .flatten()
+            .find(|x| {
+                line.graphemes
+                    .get(*x)
+                    .is_some_and(|g| is_open(g) || is_close(g))
+            })
+            .map(|x| Point::new(x, self.cursor.y))
+    }
+
+    pub fn find_matching_bracket(&mut self, point: Point) -> Option<Point> {
+        let grapheme = self.get_char_at(point)?;
+        let forward = is_open(&grapheme);
+        let context = self.context(point);
+        let found = self.find_unbalanced(point, forward, context)?;
+
+        let other = self.get_char_at(found)?;
+        let matches = if forward {
+            is_pair(&grapheme, &other)
+        } else {
+            is_pair(&other, &grapheme)
+        };
+        matches.then_some(found)
+    }
+
+    // The bracket next to the cursor and its match, for highlighting
+    pub fn matching_brackets(&mut self) -> Option<[Point; 2]> {
+        let bracket = self.bracket_near_cursor()?;
+        let matched = self.find_matching_bracket(bracket)?;
+        Some([bracket, matched])
+    }
+
+    pub fn jump_to_matching_bracket(&mut self) {
+        if let Some([_, matched]) = self.matching_brackets() {
+            self.selection = None;
+            self.move_cursor(matched);
+            self.preferred_width =
+                self.content[matched.y].width_to(matched.x, self.settings.tab_width);
+        }
+    }
+
+    // Select the text inside the closest pair of brackets around the cursor.
+    // Selecting again takes in the brackets themselves.
+    pub fn select_inside_brackets(&mut self) {
+        let open = match self.get_char_at(self.cursor) {
+            Some(g) if is_open(&g) => Some(self.cursor),
+            _ => {
+                let context = self.context(self.cursor);
+                self.find_unbalanced(self.cursor, false, context)
+                    .filter(|p| self.get_char_at(*p).is_some_and(|g| is_open(&g)))
+            }
+        };
+        let Some(open) = open else {
+            return;
+        };
+        let Some(close) = self.find_matching_bracket(open) else {
+            return;
+        };
+
+        let inside = [Point::new(open.x + 1, open.y), close];
+        let selection = match self.selection {
+            Some(mut current) => {
+                current.sort_unstable();
+                if current == inside {
+                    [open, Point::new(close.x + 1, close.y)]
+                } else {
+                    inside
+                }
+            }
+            None => inside,
+        };
+
+        self.selection = Some(selection);
+        self.move_cursor(selection[1]);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::clipboard::{ClipboardHistory, copy_with_command, copy_with_osc52, paste_with_command};
+use crate::config::data_dir;
+use crate::editor::core::Editor;
+use crate::types::line::{lines_from_text, lines_to_text};
+use crate::types::{Cursor, Line, Status};
+use std::path::PathBuf;
+
+// What the last paste inserted, so that cycling can swap it for another entry
+pub struct LastPaste {
+    // Index into the clipboard history
+    pub index: usize,
+    // Cursors as they were before pasting, the primary one first
+    pub cursors: Vec<Cursor>,
+    // Undo position right after pasting
+    pub undo_index: usize,
+}
+
+fn history_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("clipboard.toml"))
+}
+
+impl Editor {
+    pub fn load_clipboard_history(&mut self) {
+        let Some(path) = history_path() else {
+            return;
+        };
+        match ClipboardHistory::load(&path, self.config.clipboard.history_size) {
+            Ok(history) => self.clipboard = history,
+            Err(e) => self.status = Some(Status::new(format!("Clipboard history: {}", e))),
+        }
+    }
+
+    fn save_clipboard_history(&mut self) {
+        if !self.config.clipboard.persist_history {
+            return;
+        }
+        if let Some(path) = history_path()
+            && let Err(e) = self.clipboard.save(&path)
+        {
+            self.status = Some(Status::new(format!("Clipboard history: {}", e)));
+        }
+    }
+
+    // Add copied lines to the history and the system clipboard
+    pub fn push_clipboard(&mut self, lines: &[Line]) {
+        self.clipboard.push(lines_to_text(lines));
+        self.save_clipboard_history();
+        self.export_clipboard();
+    }
+
+    // Put the newest history entry on the system clipboard as well
+    pub fn export_clipboard(&mut self) {
+        let Some(text) = self.clipboard.latest() else {
+            return;
+        };
+        let settings = &self.config.clipboard;
+
+        let mut errors = vec![];
+        if let Some(command) = &settings.copy_command
+            && let Err(e) = copy_with_command(command, text)
+        {
+            errors.push(e);
+        }
+        if settings.osc52
+            && let Err(e) = copy_with_osc52(text)
+        {
+            errors.push(e.to_string());
+        }
+
+        if !errors.is_empty() {
+            self.status = Some(Status::new(format!("Clipboard: {}", errors.join(", "))));
+        }
+    }
+
+    // Add the system clipboard to the history, if there is a paste command.
+    // On failure the history is used as it is.
+    pub fn import_clipboard(&mut self) {
+        let Some(command) = &self.config.clipboard.paste_command else {
+            return;
+        };
+
+        match paste_with_command(command) {
+            Ok(text) => {
+                if self.clipboard.latest() != Some(&text) && !text.is_empty() {
+                    self.clipboard.push(text);
+                    self.save_clipboard_history();
+                }
+            }
+            Err(e) => self.status = Some(Status::new(format!("Clipboard: {}", e))),
+        }
+    }
+
+    pub fn paste(&mut self) {
+        self.import_clipboard();
+        self.paste_entry(0);
+    }
+
+    // Paste at every cursor. With as many lines as cursors, each cursor
+    // gets its own line, which is how block selections paste.
+    pub fn paste_at_cursors(&mut self, lines: Vec<Line>) {
+        if !self.extra_cursors.is_empty() && lines.len() == self.extra_cursors.len() + 1 {
+            self.for_each_cursor_indexed(|e, i| e.paste_lines(vec![lines[i].clone()]));
+        } else {
+            self.for_each_cursor(|e| e.paste_lines(lines.clone()));
+        }
+    }
+
+    // Paste a history entry at every cursor
+    pub fn paste_entry(&mut self, index: usize) {
+        let Some(text) = self.clipboard.entries.get(index) else {
+            return;
+        };
+        let lines = lines_from_text(text);
+
+        let mut cursors = vec![self.primary_cursor()];
+        cursors.extend(self.extra_cursors.iter().copied());
+        self.paste_at_cursors(lines);
+
+        self.last_paste = Some(LastPaste {
+            index,
+            cursors,
+            undo_index: self.undo_stack.index,
+        });
+    }
+
+    // Replace the text just pasted with the next older history entry
+    pub fn cycle_paste(&mut self) {
+        let Some(last) = self
+            .last_paste
+            .take()
+            .filter(|last| last.undo_index == self.undo_stack.index)
+        else {
+            self.status = Some(Status::new("Paste something first".to_string()));
+            return;
+        };
+
+        let count = self.clipboard.len();
+        if count < 2 {
+            self.last_paste = Some(last);
+            self.status = Some(Status::new("No older clipboard entries".to_string()));
+            return;
+        }
+
+        self.undo();
+        let mut cursors = last.cursors.into_iter();
+        if let Some(primary) = cursors.next() {
+            self.set_primary_cursor(primary);
+        }
+        self.extra_cursors = cursors.collect();
+
+        let index = (last.index + 1) % count;
+        self.paste_entry(index);
+        self.status = Some(Status::new(format!(
+            "Clipboard entry {}/{}",
+            index + 1,
+            count
+        )));
+    }
+}
@@ -0,0 +1,274 @@
+use crate::editor::core::{Editor, Motion};
+
+// A named action that can be bound to keys
+pub struct Command {
+    pub name: &'static str,
+    pub run: fn(&mut Editor),
+}
+
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "quit",
+        run: |e| e.should_quit = true,
+    },
+    Command {
+        name: "save",
+        run: Editor::save_file,
+    },
+    Command {
+        name: "undo",
+        run: Editor::undo,
+    },
+    Command {
+        name: "redo",
+        run: Editor::redo,
+    },
+    Command {
+        name: "copy",
+        run: Editor::copy,
+    },
+    Command {
+        name: "cut",
+        run: Editor::cut,
+    },
+    Command {
+        name: "paste",
+        run: Editor::paste,
+    },
+    Command {
+        name: "paste.cycle",
+        run: Editor::cycle_paste,
+    },
+    Command {
+        name: "paste.history",
+        run: Editor::open_clipboard_picker,
+    },
+    Command {
+        name: "edit.backspace",
+        run: Editor::remove_char,
+    },
+    Command {
+        name: "edit.newline",
+        run: Editor::insert_newline,
+    },
+    Command {
+        name: "edit.tab",
+        run: Editor::indent_or_insert_tab,
+    },
+    Command {
+        name: "edit.indent",
+        run: Editor::indent,
+    },
+    Command {
+        name: "edit.outdent",
+        run: Editor::outdent,
+    },
+    Command {
+        name: "line.duplicate",
+        run: Editor::duplicate,
+    },
+    Command {
+        name: "line.move_up",
+        run: |e| e.move_lines(true),
+    },
+    Command {
+        name: "line.move_down",
+        run: |e| e.move_lines(false),
+    },
+    Command {
+        name: "line.delete",
+        run: Editor::delete_lines,
+    },
+    Command {
+        name: "line.join",
+        run: Editor::join_lines,
+    },
+    Command {
+        name: "edit.toggle_comment",
+        run: Editor::toggle_line_comment,
+    },
+    Command {
+        name: "edit.toggle_block_comment",
+        run: Editor::toggle_block_comment,
+    },
+    Command {
+        name: "edit.toggle_soft_tabs",
+        run: Editor::toggle_soft_tabs,
+    },
+    Command {
+        name: "edit.indent_to_spaces",
+        run: |e| e.convert_indentation(true),
+    },
+    Command {
+        name: "edit.indent_to_tabs",
+        run: |e| e.convert_indentation(false),
+    },
+    Command {
+        name: "view.toggle_whitespace",
+        run: Editor::toggle_whitespace,
+    },
+    Command {
+        name: "view.toggle_indent_guides",
+        run: Editor::toggle_indent_guides,
+    },
+    Command {
+        name: "move.up",
+        run: |e| e.handle_motion(Motion::Up, false),
+    },
+    Command {
+        name: "move.down",
+        run: |e| e.handle_motion(Motion::Down, false),
+    },
+    Command {
+        name: "move.left",
+        run: |e| e.handle_motion(Motion::Left, false),
+    },
+    Command {
+        name: "move.right",
+        run: |e| e.handle_motion(Motion::Right, false),
+    },
+    Command {
+        name: "move.word_left",
+        run: |e| e.handle_motion(Motion::WordLeft, false),
+    },
+    Command {
+        name: "move.word_right",
+        run: |e| e.handle_motion(Motion::WordRight, false),
+    },
+    Command {
+        name: "move.line_start",
+        run: |e| e.handle_motion(Motion::LineStart, false),
+    },
+    Command {
+        name: "move.line_end",
+        run: |e| e.handle_motion(Motion::LineEnd, false),
+    },
+    Command {
+        name: "move.file_start",
+        run: |e| e.handle_motion(Motion::FileStart, false),
+    },
+    Command {
+        name: "move.file_end",
+        run: |e| e.handle_motion(Motion::FileEnd, false),
+    },
+    Command {
+        name: "select.up",
+        run: |e| e.handle_motion(Motion::Up, true),
+    },
+    Command {
+        name: "select.down",
+        run: |e| e.handle_motion(Motion::Down, true),
+    },
+    Command {
+        name: "select.left",
+        run: |e| e.handle_motion(Motion::Left, true),
+    },
+    Command {
+        name: "select.right",
+        run: |e| e.handle_motion(Motion::Right, true),
+    },
+    Command {
+        name: "select.word_left",
+        run: |e| e.handle_motion(Motion::WordLeft, true),
+    },
+    Command {
+        name: "select.word_right",
+        run: |e| e.handle_motion(Motion::WordRight, true),
+    },
+    Command {
+        name: "select.line_start",
+        run: |e| e.handle_motion(Motion::LineStart, true),
+    },
+    Command {
+        name: "select.line_end",
+        run: |e| e.handle_motion(Motion::LineEnd, true),
+    },
+    Command {
+        name: "select.file_start",
+        run: |e| e.handle_motion(Motion::FileStart, true),
+    },
+    Command {
+        name: "select.file_end",
+        run: |e| e.handle_motion(Motion::FileEnd, true),
+    },
+    Command {
+        name: "move.matching_bracket",
+        run: Editor::jump_to_matching_bracket,
+    },
+    Command {
+        name: "select.inside_brackets",
+        run: Editor::select_inside_brackets,
+    },
+    Command {
+        name: "select.line",
+        run: Editor::select_line,
+    },
+    Command {
+        name: "select.all",
+        run: Editor::select_all,
+    },
+    Command {
+        name: "select.block_up",
+        run: |e| e.extend_block(Motion::Up),
+    },
+    Command {
+        name: "select.block_down",
+        run: |e| e.extend_block(Motion::Down),
+    },
+    Command {
+        name: "select.block_left",
+        run: |e| e.extend_block(Motion::Left),
+    },
+    Command {
+        name: "select.block_right",
+        run: |e| e.extend_block(Motion::Right),
+    },
+    Command {
+        name: "cursor.add_above",
+        run: |e| e.add_cursor_vertically(true),
+    },
+    Command {
+        name: "cursor.add_below",
+        run: |e| e.add_cursor_vertically(false),
+    },
+    Command {
+        name: "cursor.add_next_match",
+        run: Editor::add_cursor_at_next_match,
+    },
+    Command {
+        name: "cursor.clear",
+        run: Editor::clear_extra_cursors,
+    },
+];
+
+// Commands that run at every cursor when there are several
+fn runs_per_cursor(name: &str) -> bool {
+    matches!(name, "edit.backspace" | "edit.newline" | "edit.tab")
+        || name.starts_with("move.")
+        || (name.starts_with("select.")
+            && name != "select.all"
+            && !name.starts_with("select.block_"))
+}
+
+pub fn find_command(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+impl Editor {
+    pub fn run_command(&mut self, name: &str) {
+        if let Some(command) = find_command(name) {
+            if !name.starts_with("select.block_") {
+                self.block = None;
+            }
+            // Only a paste right before can be cycled
+            if name != "paste.cycle" {
+                self.last_paste = None;
+            }
+            if runs_per_cursor(name) {
+                self.for_each_cursor(command.run);
+            } else {
+                (command.run)(self);
+            }
+        }
+    }
+}
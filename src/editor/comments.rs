@@ -0,0 +1,154 @@
+use crate::editor::core::Editor;
+use crate::types::{Line, Point, Status};
+
+fn graphemes(text: &str) -> Vec<String> {
+    Line::from_string(text.to_string()).graphemes
+}
+
+impl Editor {
+    // Comment out the selected lines, or the current line, or uncomment them
+    // if they're all commented already. Markers line up at the smallest
+    // indentation among the lines.
+    pub fn toggle_line_comment(&mut self) {
+        let token = graphemes(self.settings.line_comment.as_deref().unwrap_or_default());
+        if token.is_empty() {
+            self.no_comment_syntax("line");
+            return;
+        }
+
+        let lines = self.selected_lines();
+        let tab_width = self.settings.tab_width;
+        let is_blank = |line: &Line| line.indent_len() == line.len();
+        let is_commented = |line: &Line| line.graphemes[line.indent_len()..].starts_with(&token);
+
+        let content = &self.content[lines.clone()];
+        let uncomment = content
+            .iter()
+            .filter(|line| !is_blank(line))
+            .all(is_commented);
+        let width = content
+            .iter()
+            .filter(|line| !is_blank(line))
+            .map(|line| line.width_to(line.indent_len(), tab_width))
+            .min()
+            .unwrap_or(0);
+
+        self.edit_lines(lines, |line| {
+            if is_blank(line) {
+                return None;
+            }
+
+            if uncomment {
+                let start = line.indent_len();
+                let mut end = start + token.len();
+                if line.graphemes.get(end).is_some_and(|g| g == " ") {
+                    end += 1;
+                }
+                Some((start..end, vec![]))
+            } else {
+                let x = (0..=line.indent_len())
+                    .find(|x| line.width_to(*x, tab_width) >= width)
+                    .unwrap_or(0);
+                let mut marker = token.clone();
+                marker.push(" ".to_string());
+                Some((x..x, marker))
+            }
+        });
+    }
+
+    // Wrap the selection, or the current line's text, in a block comment,
+    // or unwrap it if it's one already
+    pub fn toggle_block_comment(&mut self) {
+        let (open, close) = match &self.settings.block_comment {
+            Some((open, close)) if !open.is_empty() && !close.is_empty() => {
+                (graphemes(open), graphemes(close))
+            }
+            _ => {
+                self.no_comment_syntax("block");
+                return;
+            }
+        };
+
+        let [a, b] = match self.selection {
+            Some(mut selection) => {
+                selection.sort_unstable();
+                selection
+            }
+            None => {
+                let line = self.get_current_line();
+                let trailing = line
+                    .graphemes
+                    .iter()
+                    .rev()
+                    .take_while(|g| g.trim().is_empty())
+                    .count();
+                let y = self.cursor.y;
+                [
+                    Point::new(line.indent_len().min(line.len() - trailing), y),
+                    Point::new(line.len() - trailing, y),
+                ]
+            }
+        };
+
+        let starts = self.content[a.y].graphemes[a.x..].starts_with(&open);
+        let ends = self.content[b.y].graphemes[..b.x].ends_with(&close);
+        let (mut redo, mut undo) = (vec![], vec![]);
+        let mut cursor = self.cursor;
+
+        let selection = if starts && ends && (a.y < b.y || b.x - a.x >= open.len() + close.len()) {
+            // Remove the markers along with the spaces padding them
+            let line = &self.content[b.y].graphemes;
+            let mut close_start = b.x - close.len();
+            // On another line than the opening marker any column will do
+            let after_open = (a.y < b.y && close_start > 0) || close_start > a.x + open.len();
+            if after_open && line[close_start - 1] == " " {
+                close_start -= 1;
+            }
+            cursor = Self::shift_point(cursor, b.y, &(close_start..b.x), 0);
+            self.replace_in_line(b.y, close_start..b.x, vec![], &mut redo, &mut undo);
+
+            let line = &self.content[a.y].graphemes;
+            let mut open_end = a.x + open.len();
+            if line.get(open_end).is_some_and(|g| g == " ") {
+                open_end += 1;
+            }
+            cursor = Self::shift_point(cursor, a.y, &(a.x..open_end), 0);
+            self.replace_in_line(a.y, a.x..open_end, vec![], &mut redo, &mut undo);
+
+            let removed = if a.y == b.y { open_end - a.x } else { 0 };
+            [a, Point::new(close_start - removed, b.y)]
+        } else {
+            let mut close = close;
+            close.insert(0, " ".to_string());
+            let mut open = open;
+            open.push(" ".to_string());
+            let (open_len, close_len) = (open.len(), close.len());
+
+            cursor = Self::shift_point(cursor, b.y, &(b.x..b.x), close_len);
+            self.replace_in_line(b.y, b.x..b.x, close, &mut redo, &mut undo);
+            cursor = Self::shift_point(cursor, a.y, &(a.x..a.x), open_len);
+            self.replace_in_line(a.y, a.x..a.x, open, &mut redo, &mut undo);
+
+            let added = if a.y == b.y { open_len } else { 0 };
+            [a, Point::new(b.x + added + close_len, b.y)]
+        };
+
+        undo.reverse();
+        self.undo_stack.add_group(redo, undo);
+        // Keep the comment selected so toggling again removes it
+        if self.selection.is_some() {
+            self.selection = Some(selection);
+            self.move_cursor(selection[1]);
+        } else {
+            self.move_cursor(cursor);
+        }
+    }
+
+    fn no_comment_syntax(&mut self, kind: &str) {
+        self.status = Some(Status::new(format!(
+            "No {} comments for {}",
+            kind,
+            self.highlighter.language.name()
+        )));
+    }
+}
@@ -1,96 +1,239 @@
 use crate::action::UndoStack;
-use crate::types::{Line, Point, Status};
-use crossterm::{
-    event::{
-        self, Event, KeyCode, KeyEvent, KeyModifiers, KeyboardEnhancementFlags,
-        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
-    },
-    execute, terminal,
-};
+use crate::clipboard::ClipboardHistory;
+use crate::config::settings::validate_tab_width;
+use crate::config::{BufferSettings, Config, editorconfig};
+use crate::editor::clipboard::LastPaste;
+use crate::editor::terminal::TerminalGuard;
+use crate::keymap::{KeyChord, Keymap};
+use crate::syntax::{Highlighter, Language};
+use crate::theme::Theme;
+use crate::types::{Charset, Cursor, FileFormat, Line, LineEnding, Point, Status};
+use crossterm::event::{self, Event};
+use std::collections::HashMap;
 use std::fs;
 use std::io;
-use std::io::stdout;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
 
-#[derive(PartialEq)]
-pub enum Direction {
+#[derive(Clone, Copy, PartialEq)]
+pub enum Motion {
     Right,
     Down,
     Left,
     Up,
+    WordLeft,
+    WordRight,
+    LineStart,
+    LineEnd,
+    FileStart,
+    FileEnd,
 }
 
 pub struct Editor {
     pub content: Vec<Line>,
     pub cursor: Point,
     pub offset: Point,
+    // The view was scrolled with the mouse wheel and is left alone until
+    // the cursor moves
+    pub scrolled: bool,
     pub preferred_width: usize,
     pub selection: Option<[Point; 2]>,
-    pub clipboard: Option<Vec<Line>>,
+    // Cursors besides the primary one above
+    pub extra_cursors: Vec<Cursor>,
+    // Corners of a block selection as (display column, line), kept while
+    // the block is being resized
+    pub block: Option<[Point; 2]>,
+    pub clipboard: ClipboardHistory,
+    pub last_paste: Option<LastPaste>,
+    // Selected entry while the clipboard history picker is open
+    pub picker: Option<usize>,
     pub undo_stack: UndoStack,
     pub filename: Option<String>,
     pub status: Option<Status>,
+    pub highlighter: Highlighter,
+    pub theme: Theme,
+    pub config: Config,
+    pub settings: BufferSettings,
+    pub format: FileFormat,
+    pub keymap: Keymap,
+    // Whether the terminal reports keys with the kitty keyboard protocol
+    pub enhanced_keyboard: bool,
+    // Keys pressed so far in a multi-key sequence
+    pub pending_keys: Vec<KeyChord>,
+    // Closing characters inserted by auto-pairing, which typing skips over
+    pub auto_closed: Vec<Point>,
+    pub should_quit: bool,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Editor {
     pub fn new() -> Self {
-        Self {
+        Self::with_config(Config::default())
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        let mut editor = Self {
             content: vec![Line::new()],
             cursor: Point::new(0, 0),
             offset: Point::new(0, 0),
+            scrolled: false,
             preferred_width: 0,
             selection: None,
-            clipboard: None,
+            extra_cursors: vec![],
+            block: None,
+            clipboard: ClipboardHistory::new(config.clipboard.history_size),
+            last_paste: None,
+            picker: None,
             undo_stack: UndoStack::new(),
             filename: None,
             status: None,
+            highlighter: Highlighter::new(Language::PlainText, 1),
+            theme: Theme::load("default").unwrap_or_default(),
+            settings: config.buffer_settings(Language::PlainText),
+            format: FileFormat::default(),
+            // The config has already been validated, so this only fails for Config::default()
+            keymap: Keymap::with_overrides(&config.keys).unwrap_or_default(),
+            enhanced_keyboard: true,
+            pending_keys: vec![],
+            auto_closed: vec![],
+            should_quit: false,
+            config,
+        };
+
+        if editor.config.clipboard.persist_history {
+            editor.load_clipboard_history();
         }
+        editor
     }
 
     pub fn load_file(&mut self, filename: &str) -> io::Result<()> {
-        match fs::read_to_string(filename) {
-            Ok(content) => {
-                self.content = content
-                    .lines()
-                    .map(|line| Line::from_string(line.to_string()))
-                    .collect();
-
-                if self.content.is_empty() {
-                    self.content.push(Line::new());
-                }
-                self.filename = Some(filename.to_string());
-                Ok(())
-            }
-            Err(_) => {
-                // File doesn't exist, start with empty content
-                self.content = vec![Line::new()];
-                self.filename = Some(filename.to_string());
-                Ok(())
-            }
+        let editorconfig = editorconfig::properties_for(Path::new(filename));
+        let charset = editorconfig.get("charset").and_then(|c| Charset::parse(c));
+
+        let (lines, format) = match fs::read(filename) {
+            Ok(bytes) => FileFormat::decode(&bytes, charset),
+            // File doesn't exist, start with empty content
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (vec![], FileFormat::default()),
+            Err(e) => return Err(e),
+        };
+
+        self.content = lines.into_iter().map(Line::from_string).collect();
+        if self.content.is_empty() {
+            self.content.push(Line::new());
+        }
+
+        self.filename = Some(filename.to_string());
+        self.format = format;
+        self.set_language(Language::from_filename(filename));
+        self.apply_editorconfig(&editorconfig);
+        Ok(())
+    }
+
+    fn set_language(&mut self, language: Language) {
+        self.highlighter = Highlighter::new(language, self.content.len());
+        self.settings = self.config.buffer_settings(language);
+    }
+
+    // Apply .editorconfig properties on top of the config file settings.
+    // Invalid values are ignored, as the spec asks.
+    fn apply_editorconfig(&mut self, properties: &HashMap<String, String>) {
+        let number = |key: &str| {
+            properties
+                .get(key)
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|n| validate_tab_width(*n).is_ok())
+        };
+
+        match properties.get("indent_style").map(String::as_str) {
+            Some("space") => self.settings.insert_spaces = true,
+            Some("tab") => self.settings.insert_spaces = false,
+            _ => (),
+        }
+
+        if properties.get("indent_size").is_some_and(|v| v == "tab") {
+            self.settings.indent_size = None;
+        } else if let Some(indent_size) = number("indent_size") {
+            self.settings.indent_size = Some(indent_size);
+            self.settings.tab_width = indent_size;
+        }
+
+        if let Some(tab_width) = number("tab_width") {
+            self.settings.tab_width = tab_width;
+        }
+
+        if let Some(line_ending) = properties
+            .get("end_of_line")
+            .and_then(|v| LineEnding::parse(v))
+        {
+            self.format.line_ending = line_ending;
+        }
+        if let Some(charset) = properties.get("charset").and_then(|v| Charset::parse(v)) {
+            self.format.charset = charset;
+        }
+
+        match properties
+            .get("trim_trailing_whitespace")
+            .map(String::as_str)
+        {
+            Some("true") => self.settings.trim_trailing_whitespace = true,
+            Some("false") => self.settings.trim_trailing_whitespace = false,
+            _ => (),
+        }
+        match properties.get("insert_final_newline").map(String::as_str) {
+            Some("true") => self.format.final_newline = true,
+            Some("false") => self.format.final_newline = false,
+            _ => (),
         }
     }
 
     pub fn save_file(&mut self) {
-        if let Some(filename) = &self.filename {
-            let content = self
-                .content
-                .iter()
-                .map(|line| {
-                    line.graphemes
-                        .iter()
-                        .map(|g| g.as_str())
-                        .collect::<String>()
-                })
-                .collect::<Vec<_>>()
-                .join("\n");
-
-            if let Err(e) = fs::write(filename, content) {
-                self.status = Some(Status::new(format!("Error saving file: {}", e)));
-            } else {
-                self.status = Some(Status::new(format!("Saved to {}", filename)));
-            }
-        } else {
+        let Some(filename) = self.filename.clone() else {
             self.status = Some(Status::new("No filename specified".to_string()));
+            return;
+        };
+
+        // Trim the buffer itself, as an undoable step, so it matches the file
+        if self.settings.trim_trailing_whitespace {
+            // Moving the cursor back would scroll a view scrolled away from it
+            let (offset, scrolled) = (self.offset, self.scrolled);
+            self.edit_lines(0..self.content.len(), |line| {
+                let trailing = line
+                    .graphemes
+                    .iter()
+                    .rev()
+                    .take_while(|g| *g == " " || *g == "\t")
+                    .count();
+                (trailing > 0).then(|| (line.len() - trailing..line.len(), vec![]))
+            });
+            if scrolled {
+                self.offset = offset;
+                self.scrolled = true;
+            }
         }
+
+        let lines: Vec<String> = self
+            .content
+            .iter()
+            .map(|line| line.graphemes.concat())
+            .collect();
+
+        let result = self
+            .format
+            .encode(&lines)
+            .and_then(|bytes| fs::write(&filename, bytes).map_err(|e| e.to_string()));
+
+        if result.is_ok() {
+            self.undo_stack.mark_saved();
+        }
+        self.status = Some(Status::new(match result {
+            Ok(()) => format!("Saved to {}", filename),
+            Err(e) => format!("Error saving file: {}", e),
+        }));
     }
 
     pub fn get_current_line(&self) -> &Line {
@@ -100,64 +243,55 @@ impl Editor {
     }
 
     pub fn run(&mut self) -> io::Result<()> {
-        terminal::enable_raw_mode()?;
-        execute!(stdout(), terminal::EnterAlternateScreen)?;
-        let mut stdout = stdout();
-        execute!(
-            stdout,
-            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
-        )?;
+        let guard = TerminalGuard::enter()?;
+        self.set_keyboard_mode(guard.enhanced_keyboard);
 
-        // Enable mouse support
-        execute!(stdout, crossterm::event::EnableMouseCapture)?;
+        match panic::catch_unwind(AssertUnwindSafe(|| self.event_loop())) {
+            Ok(result) => result,
+            Err(payload) => {
+                // The panic hook has already restored the terminal and
+                // printed the message
+                drop(guard);
+                match self.emergency_save() {
+                    Some(path) => eprintln!("fedit: unsaved changes written to {}", path.display()),
+                    None if self.undo_stack.is_modified() => {
+                        eprintln!("fedit: unsaved changes could not be written")
+                    }
+                    None => (),
+                }
+                panic::resume_unwind(payload)
+            }
+        }
+    }
 
+    fn event_loop(&mut self) -> io::Result<()> {
         // Initial draw
         self.draw()?;
 
         loop {
             match event::read()? {
-                Event::Key(KeyEvent {
-                    code, modifiers, ..
-                }) => {
-                    match (code, modifiers) {
-                        (KeyCode::Char('q'), KeyModifiers::CONTROL) => break,
-                        (KeyCode::Char('s'), KeyModifiers::CONTROL) => self.save_file(),
-                        (KeyCode::Char('c'), KeyModifiers::CONTROL) => self.copy(),
-                        (KeyCode::Char('v'), KeyModifiers::CONTROL) => self.paste(),
-                        (KeyCode::Char('z'), KeyModifiers::CONTROL) => self.undo(),
-                        (KeyCode::Char('y'), KeyModifiers::CONTROL) => self.redo(),
-                        (KeyCode::Up, mods) => self.handle_movement_input(Direction::Up, mods),
-                        (KeyCode::Down, mods) => self.handle_movement_input(Direction::Down, mods),
-                        (KeyCode::Left, mods) => self.handle_movement_input(Direction::Left, mods),
-                        (KeyCode::Right, mods) => {
-                            self.handle_movement_input(Direction::Right, mods)
-                        }
-                        (KeyCode::Backspace, _) => self.remove_char(),
-                        (KeyCode::Tab, _) => self.insert_char('\t'),
-                        (KeyCode::Enter, _) => self.insert_newline(),
-                        (KeyCode::Char(c), KeyModifiers::NONE) => {
-                            self.insert_char(c);
-                        }
-                        (KeyCode::Char(c), KeyModifiers::SHIFT) => {
-                            self.insert_char(c);
-                        }
-                        _ => {}
+                Event::Key(key_event) => {
+                    self.handle_key_event(key_event);
+                    if self.should_quit {
+                        break;
                     }
                     self.draw()?;
                 }
-                Event::Mouse(mouse_event) => {
-                    if self.handle_mouse_event(mouse_event) {
-                        self.draw()?;
-                    };
+                Event::Resize(_, _) => {
+                    self.handle_resize();
+                    self.draw()?;
+                }
+                Event::Mouse(mouse_event) if self.handle_mouse_event(mouse_event) => {
+                    self.draw()?;
+                }
+                Event::Paste(text) => {
+                    self.handle_paste_event(&text);
+                    self.draw()?;
                 }
                 _ => {}
             }
         }
 
-        execute!(stdout, crossterm::event::DisableMouseCapture)?;
-        execute!(stdout, PopKeyboardEnhancementFlags)?;
-        execute!(stdout, terminal::LeaveAlternateScreen)?;
-        terminal::disable_raw_mode()?;
         Ok(())
     }
 }
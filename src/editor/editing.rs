@@ -1,11 +1,19 @@
 use crate::action::{Action, ActionType};
 use crate::editor::core::Editor;
 use crate::types::{Line, Point};
+use std::ops::Range;
 use unicode_segmentation::UnicodeSegmentation;
 
 impl Editor {
     pub fn apply_action(&mut self, action: &Action) {
         let start = action.start;
+        self.track_auto_closed(action);
+        for cursor in &mut self.extra_cursors {
+            cursor.position = action.transform(cursor.position);
+            cursor.selection = cursor
+                .selection
+                .map(|points| points.map(|p| action.transform(p)));
+        }
 
         match action.kind {
             ActionType::Insert => {
@@ -22,6 +30,7 @@ impl Editor {
                 };
 
                 let payload = action.payload.clone().unwrap();
+                self.highlighter.edit(start.y, 1, payload.len());
 
                 match payload.len() {
                     0 => unreachable!(),
@@ -35,7 +44,7 @@ impl Editor {
                         let x_new = new_line.len();
                         new_line.graphemes.extend(right.to_vec());
                         self.content[start.y] = new_line;
-                        self.move_cursor(Point::new(x_new, self.cursor.y));
+                        self.move_cursor(Point::new(x_new, start.y));
                     }
                     _ => {
                         let mut new_lines = vec![];
@@ -56,7 +65,7 @@ impl Editor {
 
                         new_last_line.graphemes.extend(right.to_vec());
                         new_lines.push(new_last_line);
-                        let y_new = self.cursor.y + new_lines.len() - 1;
+                        let y_new = start.y + new_lines.len() - 1;
 
                         self.content.remove(start.y);
                         self.content.splice(start.y..start.y, new_lines);
@@ -67,6 +76,7 @@ impl Editor {
             }
             ActionType::Remove => {
                 let end = action.end.unwrap();
+                self.highlighter.edit(start.y, end.y - start.y + 1, 1);
 
                 match end.y - start.y {
                     0 => {
@@ -100,15 +110,16 @@ impl Editor {
 
             // Check if this char should combine with the previous character
             // (e.g. skin tone modifiers, combining diacritics)
-            if self.cursor.x > 0 {
-                if let Some(prev_char) = line.graphemes.get_mut(self.cursor.x - 1) {
-                    let combined = format!("{}{}", prev_char, grapheme);
-                    // Check if they form a single grapheme cluster
-                    if combined.graphemes(true).count() == 1 {
-                        // They combine into one grapheme cluster
-                        *prev_char = combined.clone();
-                        return;
-                    }
+            if self.cursor.x > 0
+                && let Some(prev_char) = line.graphemes.get_mut(self.cursor.x - 1)
+            {
+                let combined = format!("{}{}", prev_char, grapheme);
+                // Check if they form a single grapheme cluster
+                if combined.graphemes(true).count() == 1 {
+                    // They combine into one grapheme cluster
+                    *prev_char = combined.clone();
+                    self.highlighter.edit(self.cursor.y, 1, 1);
+                    return;
                 }
             }
 
@@ -133,88 +144,352 @@ impl Editor {
         }
     }
 
+    // Insert a tab, or spaces up to the next indentation stop with soft tabs
+    pub fn insert_tab(&mut self) {
+        if !self.settings.insert_spaces {
+            self.insert_char('\t');
+            return;
+        }
+
+        let indent_width = self.settings.indent_width();
+        let column = self
+            .get_current_line()
+            .width_to(self.cursor.x, self.settings.tab_width);
+        let count = indent_width - column % indent_width;
+        let start = self.cursor;
+
+        let redo = Action {
+            start,
+            end: None,
+            payload: Some(vec![Line {
+                graphemes: vec![" ".to_string(); count],
+            }]),
+            kind: ActionType::Insert,
+        };
+
+        let undo = Action {
+            start,
+            end: Some(Point::new(start.x + count, start.y)),
+            payload: None,
+            kind: ActionType::Remove,
+        };
+
+        self.apply_action(&redo);
+        self.undo_stack.add(redo, undo);
+    }
+
+    // Insert a line break, carrying the indentation over to the new line.
+    // Between a pair of brackets the closing one moves to its own line.
     pub fn insert_newline(&mut self) {
         let start = self.cursor;
+        let line = self.get_current_line();
+
+        let mut payload = vec![Line::new(), Line::new()];
+        let mut cursor = None;
+
+        if self.settings.auto_indent {
+            let indent = line.graphemes[..line.indent_len().min(start.x)].to_vec();
+            let before = line.graphemes[..start.x]
+                .iter()
+                .rev()
+                .find(|g| !g.trim().is_empty());
+            let after = line.graphemes[start.x..].first();
+
+            let indent_after = self.settings.indent_after.as_deref().unwrap_or_default();
+            let opens = before.is_some_and(|g| indent_after.contains(g.as_str()));
+
+            let mut inner = indent.clone();
+            if opens {
+                inner.extend(self.settings.indent_unit());
+            }
+
+            if let (true, Some(open), Some(close)) = (opens, before, after)
+                && closing_bracket(open) == Some(close.as_str())
+            {
+                cursor = Some(Point::new(inner.len(), start.y + 1));
+                payload = vec![
+                    Line::new(),
+                    Line { graphemes: inner },
+                    Line { graphemes: indent },
+                ];
+            } else {
+                payload[1].graphemes = inner;
+            }
+        }
 
         let redo = Action {
-            start: Point::new(start.x, start.y),
+            start,
             end: None,
-            payload: Some(vec![Line::new(), Line::new()]),
+            payload: Some(payload),
             kind: ActionType::Insert,
         };
 
         self.apply_action(&redo);
 
         let undo = Action {
-            start: Point::new(start.x, start.y),
-            end: Some(Point::new(self.cursor.x, self.cursor.y)),
+            start,
+            end: Some(self.cursor),
             payload: None,
             kind: ActionType::Remove,
         };
 
         self.undo_stack.add(redo, undo);
+        if let Some(cursor) = cursor {
+            self.move_cursor(cursor);
+        }
     }
 
     pub fn get_char_at(&self, point: Point) -> Option<String> {
         self.content.get(point.y)?.graphemes.get(point.x).cloned()
     }
 
-    pub fn remove_char(&mut self) {
-        if let Some(start) = self.get_previous_point() {
-            let redo = Action {
+    // Replace part of a single line, applying the change and recording the
+    // actions for it so several edits can be grouped into one undo step
+    pub fn replace_in_line(
+        &mut self,
+        y: usize,
+        range: Range<usize>,
+        graphemes: Vec<String>,
+        redo: &mut Vec<Action>,
+        undo: &mut Vec<Action>,
+    ) {
+        let removed = self.content[y].graphemes[range.clone()].to_vec();
+        let start = Point::new(range.start, y);
+        let inserted_end = Point::new(range.start + graphemes.len(), y);
+
+        if !removed.is_empty() {
+            let action = Action {
                 start,
-                end: Some(self.cursor),
+                end: Some(Point::new(range.end, y)),
                 payload: None,
                 kind: ActionType::Remove,
             };
-
-            let undo = Action {
+            self.apply_action(&action);
+            redo.push(action);
+        }
+        if !graphemes.is_empty() {
+            let action = Action {
                 start,
                 end: None,
-                payload: Some(vec![Line {
-                    graphemes: vec![self.get_char_at(start).unwrap()],
-                }]),
+                payload: Some(vec![Line { graphemes }]),
                 kind: ActionType::Insert,
             };
+            self.apply_action(&action);
+            redo.push(action);
+        }
 
-            self.apply_action(&redo);
-            self.undo_stack.add(redo, undo);
+        // Inverses, in the order they'll be applied after reversing `undo`
+        if !removed.is_empty() {
+            undo.push(Action {
+                start,
+                end: None,
+                payload: Some(vec![Line { graphemes: removed }]),
+                kind: ActionType::Insert,
+            });
+        }
+        if inserted_end != start {
+            undo.push(Action {
+                start,
+                end: Some(inserted_end),
+                payload: None,
+                kind: ActionType::Remove,
+            });
+        }
+    }
+
+    // Insert text and record the actions for it, like `replace_in_line`
+    pub fn insert_text(
+        &mut self,
+        at: Point,
+        lines: Vec<Line>,
+        redo: &mut Vec<Action>,
+        undo: &mut Vec<Action>,
+    ) {
+        let end = match lines.as_slice() {
+            [line] => Point::new(at.x + line.len(), at.y),
+            _ => Point::new(lines.last().map_or(0, Line::len), at.y + lines.len() - 1),
+        };
+
+        let action = Action {
+            start: at,
+            end: None,
+            payload: Some(lines),
+            kind: ActionType::Insert,
+        };
+        self.apply_action(&action);
+        redo.push(action);
+        undo.push(Action {
+            start: at,
+            end: Some(end),
+            payload: None,
+            kind: ActionType::Remove,
+        });
+    }
+
+    // Remove the text between two points and record the actions for it
+    pub fn remove_text(
+        &mut self,
+        start: Point,
+        end: Point,
+        redo: &mut Vec<Action>,
+        undo: &mut Vec<Action>,
+    ) {
+        let removed = self.text_between(start, end);
+        let action = Action {
+            start,
+            end: Some(end),
+            payload: None,
+            kind: ActionType::Remove,
+        };
+        self.apply_action(&action);
+        redo.push(action);
+        undo.push(Action {
+            start,
+            end: None,
+            payload: Some(removed),
+            kind: ActionType::Insert,
+        });
+    }
+
+    // Where a point ends up after `range` on line `y` is replaced by
+    // `inserted` graphemes
+    pub fn shift_point(point: Point, y: usize, range: &Range<usize>, inserted: usize) -> Point {
+        if point.y != y || point.x <= range.start {
+            point
+        } else if point.x >= range.end {
+            Point::new(point.x + inserted - range.len(), y)
+        } else {
+            Point::new(range.start + inserted.min(point.x - range.start), y)
         }
     }
 
-    pub fn paste(&mut self) {
-        if let Some(clipboard) = &self.clipboard {
-            let start = self.cursor;
+    // Backspace over soft tab indentation removes spaces back to the
+    // previous indentation stop
+    fn soft_tab_start(&self) -> Option<Point> {
+        let line = self.get_current_line();
+        let x = self.cursor.x;
+        if !self.settings.insert_spaces || x == 0 || x > line.indent_len() {
+            return None;
+        }
+
+        let indent_width = self.settings.indent_width();
+        let column = line.width_to(x, self.settings.tab_width);
+        let target = (column - 1) / indent_width * indent_width;
+        let spaces = line.graphemes[..x]
+            .iter()
+            .rev()
+            .take_while(|g| *g == " ")
+            .count()
+            .min(column - target);
+
+        (spaces > 1).then(|| Point::new(x - spaces, self.cursor.y))
+    }
 
+    pub fn remove_char(&mut self) {
+        if self.delete_selection() || self.remove_empty_pair() {
+            return;
+        }
+
+        if let Some(start) = self.soft_tab_start() {
+            let end = self.cursor;
             let redo = Action {
+                start,
+                end: Some(end),
+                payload: None,
+                kind: ActionType::Remove,
+            };
+
+            let undo = Action {
                 start,
                 end: None,
-                payload: Some(clipboard.clone()),
+                payload: Some(vec![Line {
+                    graphemes: vec![" ".to_string(); end.x - start.x],
+                }]),
                 kind: ActionType::Insert,
             };
 
             self.apply_action(&redo);
-
-            let undo = Action {
+            self.undo_stack.add(redo, undo);
+        } else if let Some(start) = self.get_previous_point() {
+            let redo = Action {
                 start,
                 end: Some(self.cursor),
                 payload: None,
                 kind: ActionType::Remove,
             };
 
+            // Removing at the start of a line joins it with the previous one
+            let payload = match self.get_char_at(start) {
+                Some(grapheme) => vec![Line {
+                    graphemes: vec![grapheme],
+                }],
+                None => vec![Line::new(), Line::new()],
+            };
+
+            let undo = Action {
+                start,
+                end: None,
+                payload: Some(payload),
+                kind: ActionType::Insert,
+            };
+
+            self.apply_action(&redo);
             self.undo_stack.add(redo, undo);
         }
     }
 
+    // Insert text at the cursor, replacing the selection
+    pub fn paste_lines(&mut self, lines: Vec<Line>) {
+        self.undo_stack.begin_group();
+        self.delete_selection();
+
+        let start = self.cursor;
+        let redo = Action {
+            start,
+            end: None,
+            payload: Some(lines),
+            kind: ActionType::Insert,
+        };
+
+        self.apply_action(&redo);
+
+        let undo = Action {
+            start,
+            end: Some(self.cursor),
+            payload: None,
+            kind: ActionType::Remove,
+        };
+
+        self.undo_stack.add(redo, undo);
+        self.undo_stack.end_group();
+    }
+
     pub fn undo(&mut self) {
-        if let Some(action) = self.undo_stack.undo() {
-            self.apply_action(&action);
+        if let Some(actions) = self.undo_stack.undo() {
+            self.selection = None;
+            self.extra_cursors.clear();
+            for action in &actions {
+                self.apply_action(action);
+            }
         }
     }
 
     pub fn redo(&mut self) {
-        if let Some(action) = self.undo_stack.redo() {
-            self.apply_action(&action);
+        if let Some(actions) = self.undo_stack.redo() {
+            self.selection = None;
+            self.extra_cursors.clear();
+            for action in &actions {
+                self.apply_action(action);
+            }
         }
     }
 }
+
+fn closing_bracket(open: &str) -> Option<&'static str> {
+    match open {
+        "(" => Some(")"),
+        "[" => Some("]"),
+        "{" => Some("}"),
+        _ => None,
+    }
+}
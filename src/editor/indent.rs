@@ -0,0 +1,123 @@
+use crate::editor::core::Editor;
+use crate::types::{Line, Status};
+use std::ops::Range;
+
+impl Editor {
+    pub fn toggle_soft_tabs(&mut self) {
+        self.settings.insert_spaces = !self.settings.insert_spaces;
+        let state = if self.settings.insert_spaces {
+            "spaces"
+        } else {
+            "tabs"
+        };
+        self.status = Some(Status::new(format!("Indenting with {}", state)));
+    }
+
+    // Rewrite the leading whitespace of every line with spaces or tabs,
+    // keeping its width, as a single undo step
+    pub fn convert_indentation(&mut self, spaces: bool) {
+        let tab_width = self.settings.tab_width;
+        let changed = self.edit_lines(0..self.content.len(), |line| {
+            let indent_len = line.indent_len();
+            let width = line.width_to(indent_len, tab_width);
+
+            let indent = if spaces {
+                vec![" ".to_string(); width]
+            } else {
+                let mut indent = vec!["\t".to_string(); width / tab_width];
+                indent.extend(vec![" ".to_string(); width % tab_width]);
+                indent
+            };
+            (line.graphemes[..indent_len] != indent[..]).then_some((0..indent_len, indent))
+        });
+        self.settings.insert_spaces = spaces;
+
+        let kind = if spaces { "spaces" } else { "tabs" };
+        self.status = Some(Status::new(if changed {
+            format!("Converted indentation to {}", kind)
+        } else {
+            format!("Indentation already uses {}", kind)
+        }));
+    }
+
+    // Lines touched by the selection, or the cursor line. A selection ending
+    // at the start of a line doesn't include that line.
+    pub fn selected_lines(&self) -> Range<usize> {
+        match self.selection {
+            Some(mut selection) => {
+                selection.sort_unstable();
+                let [a, b] = selection;
+                if b.x == 0 && b.y > a.y {
+                    a.y..b.y
+                } else {
+                    a.y..b.y + 1
+                }
+            }
+            None => self.cursor.y..self.cursor.y + 1,
+        }
+    }
+
+    // Tab indents the selected lines, or inserts a tab without a selection
+    pub fn indent_or_insert_tab(&mut self) {
+        if self.selection.is_some() {
+            self.indent();
+        } else {
+            self.insert_tab();
+        }
+    }
+
+    pub fn indent(&mut self) {
+        let unit = self.settings.indent_unit();
+
+        // Leave empty lines alone rather than filling them with whitespace
+        self.edit_lines(self.selected_lines(), |line| {
+            (!line.is_empty()).then(|| (0..0, unit.clone()))
+        });
+    }
+
+    pub fn outdent(&mut self) {
+        let indent_width = self.settings.indent_width();
+        self.edit_lines(self.selected_lines(), |line| {
+            let removed = match line.graphemes.first().map(String::as_str) {
+                Some("\t") => 1,
+                _ => line
+                    .graphemes
+                    .iter()
+                    .take(indent_width)
+                    .take_while(|g| *g == " ")
+                    .count(),
+            };
+            (removed > 0).then(|| (0..removed, vec![]))
+        });
+    }
+
+    // Replace part of each line in `lines` as one undo step, keeping the
+    // cursor and selection on the same text. Returns whether anything changed.
+    pub fn edit_lines(
+        &mut self,
+        lines: Range<usize>,
+        edit: impl Fn(&Line) -> Option<(Range<usize>, Vec<String>)>,
+    ) -> bool {
+        let (mut redo, mut undo) = (vec![], vec![]);
+        let mut cursor = self.cursor;
+        let mut selection = self.selection;
+
+        for y in lines {
+            let Some((range, graphemes)) = edit(&self.content[y]) else {
+                continue;
+            };
+            let inserted = graphemes.len();
+            cursor = Self::shift_point(cursor, y, &range, inserted);
+            selection =
+                selection.map(|points| points.map(|p| Self::shift_point(p, y, &range, inserted)));
+            self.replace_in_line(y, range, graphemes, &mut redo, &mut undo);
+        }
+
+        let changed = !redo.is_empty();
+        undo.reverse();
+        self.undo_stack.add_group(redo, undo);
+        self.move_cursor(cursor);
+        self.selection = selection;
+        changed
+    }
+}
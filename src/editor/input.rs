@@ -0,0 +1,87 @@
+use crate::editor::core::Editor;
+use crate::keymap::{Binding, KeyChord, Keymap};
+use crate::types::Status;
+use crate::types::line::lines_from_text;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+impl Editor {
+    // Switch to the bindings that work with the terminal's keyboard support
+    pub fn set_keyboard_mode(&mut self, enhanced: bool) {
+        self.enhanced_keyboard = enhanced;
+        // Overrides were checked when the config was validated
+        self.keymap = Keymap::for_keyboard(&self.config.keys, enhanced).unwrap_or_default();
+        if !enhanced {
+            self.status = Some(Status::new(
+                "Legacy keyboard mode, some keys use alternate bindings".to_string(),
+            ));
+        }
+    }
+
+    pub fn handle_key_event(&mut self, event: KeyEvent) {
+        if self.picker.is_some() {
+            self.handle_picker_key(event);
+            return;
+        }
+
+        self.pending_keys.push(KeyChord::from_event(&event));
+
+        match self.keymap.lookup(&self.pending_keys) {
+            Binding::Command(command) => {
+                let command = command.to_string();
+                self.pending_keys.clear();
+                self.run_command(&command);
+            }
+            Binding::Prefix => {
+                let keys: Vec<String> = self.pending_keys.iter().map(|k| k.to_string()).collect();
+                self.status = Some(Status::new(format!("{} ...", keys.join(" "))));
+            }
+            Binding::None => {
+                let sequence = self.pending_keys.len() > 1;
+                self.pending_keys.clear();
+
+                if sequence {
+                    self.status = None;
+                } else if let KeyCode::Char(c) = event.code
+                    && (event.modifiers - KeyModifiers::SHIFT).is_empty()
+                {
+                    // Unbound plain keys type text
+                    self.block = None;
+                    self.last_paste = None;
+                    self.for_each_cursor(|e| e.type_char(c));
+                }
+            }
+        }
+    }
+
+    // Bracketed paste arrives as one string. Insert it verbatim as a single
+    // action per cursor, bypassing auto-indent and auto-pairs.
+    pub fn handle_paste_event(&mut self, text: &str) {
+        self.pending_keys.clear();
+        self.picker = None;
+        self.block = None;
+        self.last_paste = None;
+
+        self.paste_at_cursors(lines_from_text(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::editor::core::Editor;
+    use crate::types::Point;
+    use crate::types::line::lines_to_text;
+
+    #[test]
+    fn paste_one_line_per_cursor() {
+        let mut e = Editor::new();
+        e.handle_paste_event("a\nb\nc");
+        e.move_cursor(Point::new(1, 0));
+        e.preferred_width = 1;
+        e.add_cursor_vertically(false);
+        e.add_cursor_vertically(false);
+
+        e.handle_paste_event("1\n2\n3");
+
+        assert_eq!(lines_to_text(&e.content), "a1\nb2\nc3");
+    }
+}
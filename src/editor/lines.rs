@@ -0,0 +1,188 @@
+use crate::editor::core::Editor;
+use crate::types::{Line, Point};
+
+impl Editor {
+    fn line_end(&self, y: usize) -> Point {
+        Point::new(self.content[y].len(), y)
+    }
+
+    // Duplicate the selected text, or the current line without a selection
+    pub fn duplicate(&mut self) {
+        let (mut redo, mut undo) = (vec![], vec![]);
+
+        match self.selection {
+            Some(mut selection) if selection[0] != selection[1] => {
+                selection.sort_unstable();
+                let [a, b] = selection;
+                let text = self.text_between(a, b);
+                self.insert_text(b, text, &mut redo, &mut undo);
+                // Select the copy
+                self.selection = Some([b, self.cursor]);
+            }
+            _ => {
+                let y = self.cursor.y;
+                let copy = vec![Line::new(), self.content[y].clone()];
+                let x = self.cursor.x;
+                self.insert_text(self.line_end(y), copy, &mut redo, &mut undo);
+                self.selection = None;
+                self.move_cursor(Point::new(x, y + 1));
+            }
+        }
+
+        undo.reverse();
+        self.undo_stack.add_group(redo, undo);
+    }
+
+    // Move the selected lines, or the current line, up or down by one
+    pub fn move_lines(&mut self, up: bool) {
+        let lines = self.selected_lines();
+        let cursor = self.cursor;
+        let (mut redo, mut undo) = (vec![], vec![]);
+
+        if up {
+            if lines.start == 0 {
+                return;
+            }
+            // Take out the line above and put it back below the block
+            let above = self.content[lines.start - 1].clone();
+            let start = Point::new(0, lines.start - 1);
+            self.remove_text(start, Point::new(0, lines.start), &mut redo, &mut undo);
+            let end = self.line_end(lines.end - 2);
+            self.insert_text(end, vec![Line::new(), above], &mut redo, &mut undo);
+        } else {
+            if lines.end >= self.content.len() {
+                return;
+            }
+            // Take out the line below and put it back above the block
+            let below = self.content[lines.end].clone();
+            let (start, end) = (self.line_end(lines.end - 1), self.line_end(lines.end));
+            self.remove_text(start, end, &mut redo, &mut undo);
+            let start = Point::new(0, lines.start);
+            self.insert_text(start, vec![below, Line::new()], &mut redo, &mut undo);
+        }
+
+        undo.reverse();
+        self.undo_stack.add_group(redo, undo);
+
+        // Points move with the block. A selection ending at the start of
+        // the line after the block ends at the block's end when that line
+        // doesn't exist anymore.
+        let last = self.content.len() - 1;
+        let shift = |p: Point| {
+            let y = if up { p.y - 1 } else { p.y + 1 };
+            if y > last {
+                Point::new(self.content[last].len(), last)
+            } else {
+                Point::new(p.x.min(self.content[y].len()), y)
+            }
+        };
+        let cursor = shift(cursor);
+        self.selection = self.selection.map(|points| points.map(shift));
+        self.move_cursor(cursor);
+    }
+
+    // Delete the selected lines, or the current line
+    pub fn delete_lines(&mut self) {
+        let lines = self.selected_lines();
+        let last = self.content.len() - 1;
+
+        // Take the line break before or after the lines along with them
+        let (start, end) = if lines.start > 0 {
+            (self.line_end(lines.start - 1), self.line_end(lines.end - 1))
+        } else if lines.end <= last {
+            (Point::zero(), Point::new(0, lines.end))
+        } else {
+            (Point::zero(), self.line_end(last))
+        };
+
+        let (mut redo, mut undo) = (vec![], vec![]);
+        self.remove_text(start, end, &mut redo, &mut undo);
+        undo.reverse();
+        self.undo_stack.add_group(redo, undo);
+
+        let y = lines.start.min(self.content.len() - 1);
+        let x = self.cursor.x.min(self.content[y].len());
+        self.selection = None;
+        self.move_cursor(Point::new(x, y));
+    }
+
+    // Join the selected lines, or the next line onto the current one,
+    // replacing the indentation and trailing whitespace between them with
+    // a single space
+    pub fn join_lines(&mut self) {
+        let lines = self.selected_lines();
+        let joins = lines.len().saturating_sub(1).max(1);
+        let y = lines.start;
+        let (mut redo, mut undo) = (vec![], vec![]);
+        let mut cursor = self.cursor;
+
+        for _ in 0..joins {
+            if y + 1 >= self.content.len() {
+                break;
+            }
+
+            let line = &self.content[y];
+            let trimmed = line.len()
+                - line
+                    .graphemes
+                    .iter()
+                    .rev()
+                    .take_while(|g| g.trim().is_empty())
+                    .count();
+            let next = &self.content[y + 1];
+            let indent = next.indent_len();
+            let space = trimmed > 0 && indent < next.len();
+
+            let start = Point::new(trimmed, y);
+            self.remove_text(start, Point::new(indent, y + 1), &mut redo, &mut undo);
+            if space {
+                let text = vec![Line::from_string(" ".to_string())];
+                self.insert_text(start, text, &mut redo, &mut undo);
+            }
+            cursor = start;
+        }
+
+        undo.reverse();
+        self.undo_stack.add_group(redo, undo);
+        self.selection = None;
+        self.move_cursor(cursor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::editor::core::Editor;
+    use crate::types::Point;
+    use crate::types::line::lines_to_text;
+
+    fn editor(text: &str) -> Editor {
+        let mut editor = Editor::new();
+        editor.handle_paste_event(text);
+        editor
+    }
+
+    #[test]
+    fn move_whole_line_selection_down_to_last_line() {
+        let mut e = editor("one\ntwo\nthree");
+        e.selection = Some([Point::new(0, 0), Point::new(0, 2)]);
+        e.cursor = Point::new(0, 2);
+
+        e.move_lines(false);
+
+        assert_eq!(lines_to_text(&e.content), "three\none\ntwo");
+        assert_eq!(e.selection, Some([Point::new(0, 1), Point::new(3, 2)]));
+        assert_eq!(e.cursor, Point::new(3, 2));
+    }
+
+    #[test]
+    fn move_whole_line_selection_up() {
+        let mut e = editor("one\ntwo\nthree");
+        e.selection = Some([Point::new(0, 1), Point::new(0, 2)]);
+        e.cursor = Point::new(0, 2);
+
+        e.move_lines(true);
+
+        assert_eq!(lines_to_text(&e.content), "two\none\nthree");
+        assert_eq!(e.selection, Some([Point::new(0, 0), Point::new(0, 1)]));
+    }
+}
@@ -1,7 +1,19 @@
+pub mod block;
+pub mod brackets;
+pub mod clipboard;
+pub mod commands;
+pub mod comments;
 pub mod core;
 pub mod editing;
+pub mod indent;
+pub mod input;
+pub mod lines;
 pub mod movement;
+pub mod multi_cursor;
+pub mod pairs;
+pub mod picker;
 pub mod rendering;
 pub mod selection;
+pub mod terminal;
 
 pub use core::Editor;
@@ -1,14 +1,22 @@
-use crate::editor::core::{Direction, Editor};
+use crate::editor::core::{Editor, Motion};
 use crate::types::Point;
 use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
+// What a mouse click landed on
+pub enum ClickTarget {
+    // A grapheme index and line in the buffer, with the display column
+    Text { x: usize, y: usize, width: usize },
+    StatusBar,
+}
+
 impl Editor {
     // Make sure the cursor stays within the viewport
     pub fn adjust_offset(&mut self) {
+        self.scrolled = false;
         let Point {
             x: dims_width,
             y: dims_height,
-        } = Editor::get_dimensions();
+        } = self.get_dimensions();
 
         if self.cursor.y < self.offset.y {
             self.offset.y = self.cursor.y;
@@ -16,7 +24,9 @@ impl Editor {
             self.offset.y = self.cursor.y - dims_height + 1;
         }
 
-        let target_width = self.get_current_line().width_to(self.cursor.x);
+        let target_width = self
+            .get_current_line()
+            .width_to(self.cursor.x, self.settings.tab_width);
         if target_width < self.offset.x {
             self.offset.x = target_width;
         } else if target_width >= self.offset.x + dims_width {
@@ -52,116 +62,112 @@ impl Editor {
         self.adjust_offset();
     }
 
-    pub fn handle_movement_input(&mut self, direction: Direction, modifiers: KeyModifiers) {
-        let current_line = self.get_current_line();
-        let point_old = self.cursor;
-        let Point { x: x_old, y: y_old } = point_old;
-
-        let (x_new, y_new) = match direction {
-            // Vertical motion
-            Direction::Up | Direction::Down => match modifiers {
-                // SUPER + UP or SUPER + DOWN
-                x if x & KeyModifiers::SUPER != KeyModifiers::NONE => {
-                    self.preferred_width = 0;
-
-                    match direction {
-                        Direction::Up => (0, 0),
-                        Direction::Down => {
-                            let y_max = self.content.len().saturating_sub(1);
-                            let x_max = self.content[y_max].len();
-
-                            (x_max, y_max)
-                        }
-                        _ => unreachable!(),
-                    }
-                }
-                // UP or DOWN
-                _ => {
-                    let dy = match direction {
-                        Direction::Up => -1,
-                        Direction::Down => 1,
-                        _ => unreachable!(),
-                    };
+    // Character class used to find word boundaries
+    fn char_class(grapheme: &str) -> u8 {
+        match grapheme.chars().next() {
+            Some(c) if c.is_whitespace() => 0,
+            Some(c) if c.is_alphanumeric() || c == '_' => 1,
+            _ => 2,
+        }
+    }
 
-                    let y_new = (y_old as isize + dy)
-                        .min(self.content.len() as isize - 1)
-                        .max(0) as usize;
+    fn word_left_point(&self) -> Point {
+        let Point { x, y } = self.cursor;
+        if x == 0 {
+            return self.get_previous_point().unwrap_or(self.cursor);
+        }
 
-                    if y_new == y_old {
-                        (x_old, y_old)
-                    } else {
-                        let new_line = &self.content[y_new as usize];
-                        let preferred_x = new_line.x_at_width(self.preferred_width);
-                        let x_new = preferred_x.map_or(new_line.len(), |p| p);
+        let graphemes = &self.content[y].graphemes[..x];
+        let mut x_new = x;
+        // Skip whitespace, then the run of characters before it
+        while x_new > 0 && Self::char_class(&graphemes[x_new - 1]) == 0 {
+            x_new -= 1;
+        }
+        if x_new > 0 {
+            let class = Self::char_class(&graphemes[x_new - 1]);
+            while x_new > 0 && Self::char_class(&graphemes[x_new - 1]) == class {
+                x_new -= 1;
+            }
+        }
 
-                        (x_new, y_new)
-                    }
-                }
-            },
-            Direction::Left | Direction::Right => match modifiers {
-                // SUPER+LEFT or SUPER+RIGHT
-                x if x & KeyModifiers::SUPER != KeyModifiers::NONE => match direction {
-                    Direction::Left => {
-                        if x_old == 0 {
-                            if y_old > 0 {
-                                let y_new = y_old - 1;
-                                let x_new = self.content[y_new].len();
-
-                                (x_new, y_new)
-                            } else {
-                                (x_old, y_old)
-                            }
-                        } else {
-                            (0, y_old)
-                        }
-                    }
-                    Direction::Right => {
-                        if x_old == current_line.len() {
-                            if y_old < self.content.len().saturating_sub(1) {
-                                let x_new = 0;
-                                let y_new = y_old + 1;
-
-                                (x_new, y_new)
-                            } else {
-                                (x_old, y_old)
-                            }
-                        } else {
-                            (current_line.len(), y_old)
-                        }
-                    }
-                    _ => unreachable!(),
-                },
-                // LEFT or RIGHT
-                _ => {
-                    let (x_new, y_new) = match direction {
-                        Direction::Left => {
-                            if let Some(Point { x: x_new, y: y_new }) = self.get_previous_point() {
-                                (x_new, y_new)
-                            } else {
-                                (x_old, y_old)
-                            }
-                        }
-                        Direction::Right => {
-                            if let Some(Point { x: x_new, y: y_new }) = self.get_next_point() {
-                                (x_new, y_new)
-                            } else {
-                                (x_old, y_old)
-                            }
-                        }
-                        _ => unreachable!(),
-                    };
+        Point::new(x_new, y)
+    }
 
-                    self.preferred_width = self.content[y_new].width_to(x_new);
+    fn word_right_point(&self) -> Point {
+        let Point { x, y } = self.cursor;
+        let graphemes = &self.content[y].graphemes;
+        if x == graphemes.len() {
+            return self.get_next_point().unwrap_or(self.cursor);
+        }
 
-                    (x_new, y_new)
+        let mut x_new = x;
+        // Skip whitespace, then the run of characters after it
+        while x_new < graphemes.len() && Self::char_class(&graphemes[x_new]) == 0 {
+            x_new += 1;
+        }
+        if x_new < graphemes.len() {
+            let class = Self::char_class(&graphemes[x_new]);
+            while x_new < graphemes.len() && Self::char_class(&graphemes[x_new]) == class {
+                x_new += 1;
+            }
+        }
+
+        Point::new(x_new, y)
+    }
+
+    // Where the cursor ends up after a motion. Horizontal motions also reset
+    // the preferred width used by vertical ones.
+    pub fn get_motion_target(&mut self, motion: Motion) -> Point {
+        let Point { x: x_old, y: y_old } = self.cursor;
+        let current_line = self.get_current_line();
+        let y_max = self.content.len().saturating_sub(1);
+
+        let point_new = match motion {
+            Motion::Up | Motion::Down => {
+                let y_new = match motion {
+                    Motion::Up => y_old.saturating_sub(1),
+                    _ => (y_old + 1).min(y_max),
+                };
+
+                if y_new == y_old {
+                    return self.cursor;
                 }
-            },
+
+                let new_line = &self.content[y_new];
+                let x_new = new_line
+                    .x_at_width(self.preferred_width, self.settings.tab_width)
+                    .unwrap_or(new_line.len());
+                return Point::new(x_new, y_new);
+            }
+            Motion::FileStart => Point::zero(),
+            Motion::FileEnd => Point::new(self.content[y_max].len(), y_max),
+            // At the start of a line, go to the end of the previous one
+            Motion::LineStart if x_old == 0 && y_old > 0 => {
+                Point::new(self.content[y_old - 1].len(), y_old - 1)
+            }
+            Motion::LineStart => Point::new(0, y_old),
+            // At the end of a line, go to the start of the next one
+            Motion::LineEnd if x_old == current_line.len() && y_old < y_max => {
+                Point::new(0, y_old + 1)
+            }
+            Motion::LineEnd => Point::new(current_line.len(), y_old),
+            Motion::Left => self.get_previous_point().unwrap_or(self.cursor),
+            Motion::Right => self.get_next_point().unwrap_or(self.cursor),
+            Motion::WordLeft => self.word_left_point(),
+            Motion::WordRight => self.word_right_point(),
         };
 
-        let point_new = Point { x: x_new, y: y_new };
+        self.preferred_width =
+            self.content[point_new.y].width_to(point_new.x, self.settings.tab_width);
+        point_new
+    }
 
-        // Selection logic
-        if modifiers & KeyModifiers::SHIFT != KeyModifiers::NONE {
+    // Move the cursor, extending the selection if `select` is set
+    pub fn handle_motion(&mut self, motion: Motion, select: bool) {
+        let point_old = self.cursor;
+        let point_new = self.get_motion_target(motion);
+
+        if select {
             self.handle_selection(point_old, point_new);
         } else {
             self.selection = None;
@@ -171,34 +177,122 @@ impl Editor {
     }
 
     pub fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> bool {
+        if self.picker.is_some() {
+            return false;
+        }
+        let alt = mouse_event.modifiers.contains(KeyModifiers::ALT);
+        let shift = mouse_event.modifiers.contains(KeyModifiers::SHIFT);
+        let lines = self.config.scroll_lines as isize;
         match mouse_event.kind {
             MouseEventKind::Down(MouseButton::Left) => {
-                let click_y = mouse_event.row as usize;
-                let click_x = mouse_event.column as usize;
-                let y_new = click_y + self.offset.y;
-
-                // If clicking the line we're on, check x offset
-                let line = &self.content[y_new];
-                let offset_width = if y_new == self.cursor.y {
-                    line.width_to(self.offset.x)
+                let (x_new, y_new, width_goal) =
+                    match self.click_target(mouse_event.column, mouse_event.row) {
+                        ClickTarget::Text { x, y, width } => (x, y, width),
+                        // Clicking the status bar dismisses its message
+                        ClickTarget::StatusBar => return self.status.take().is_some(),
+                    };
+                self.scrolled = false;
+
+                // Alt+click adds a cursor instead of moving the current one
+                self.block = None;
+                if alt {
+                    self.add_cursor(Point::new(x_new, y_new), None);
                 } else {
-                    0
+                    self.extra_cursors.clear();
+                    self.cursor = Point::new(x_new, y_new);
+                }
+                self.preferred_width = width_goal;
+                true
+            }
+            // Alt+drag selects a block from where the mouse went down
+            MouseEventKind::Drag(MouseButton::Left) if alt => {
+                // Dragging onto the status bar keeps to the last text row
+                let last_row = self.get_dimensions().y.saturating_sub(1) as u16;
+                let row = mouse_event.row.min(last_row);
+                let ClickTarget::Text {
+                    y: y_new,
+                    width: width_goal,
+                    ..
+                } = self.click_target(mouse_event.column, row)
+                else {
+                    return false;
+                };
+                let anchor = match self.block {
+                    Some([anchor, _]) => anchor,
+                    None => Point::new(
+                        self.get_current_line()
+                            .width_to(self.cursor.x, self.settings.tab_width),
+                        self.cursor.y,
+                    ),
                 };
-                let width_goal = click_x + offset_width;
+                self.set_block(anchor, Point::new(width_goal, y_new));
+                true
+            }
+            // Shift turns the wheel sideways
+            MouseEventKind::ScrollUp if shift => self.scroll_horizontally(-lines),
+            MouseEventKind::ScrollDown if shift => self.scroll_horizontally(lines),
+            MouseEventKind::ScrollUp => self.scroll(-lines),
+            MouseEventKind::ScrollDown => self.scroll(lines),
+            MouseEventKind::ScrollLeft => self.scroll_horizontally(-lines),
+            MouseEventKind::ScrollRight => self.scroll_horizontally(lines),
+            _ => false,
+        }
+    }
 
-                if y_new != self.cursor.y {
-                    self.offset.x = 0;
-                }
+    // Move the view by `lines` without moving the cursor. Returns whether
+    // anything changed.
+    pub fn scroll(&mut self, lines: isize) -> bool {
+        let Point { y: height, .. } = self.get_dimensions();
+        let max = self.content.len().saturating_sub(height.max(1));
+        let offset = self.offset.y.saturating_add_signed(lines).min(max);
 
-                self.cursor.x = line.x_at_width(width_goal).unwrap_or(line.len());
-                self.cursor.y = y_new;
-                self.preferred_width = width_goal;
+        self.scrolled = true;
+        let changed = offset != self.offset.y;
+        self.offset.y = offset;
+        changed
+    }
 
-                return true;
-            }
-            _ => (),
-        };
+    // Move the view sideways by `columns`, up to where the longest line in
+    // view ends at the right edge
+    pub fn scroll_horizontally(&mut self, columns: isize) -> bool {
+        let Point {
+            x: width,
+            y: height,
+        } = self.get_dimensions();
+        let longest = self
+            .content
+            .iter()
+            .skip(self.offset.y)
+            .take(height)
+            .map(|line| line.width_to(line.len(), self.settings.tab_width))
+            .max()
+            .unwrap_or(0);
+        // Leave a column for the cursor after the last character
+        let max = (longest + 1).saturating_sub(width).max(self.offset.x);
+        let offset = self.offset.x.saturating_add_signed(columns).min(max);
+
+        self.scrolled = true;
+        let changed = offset != self.offset.x;
+        self.offset.x = offset;
+        changed
+    }
+
+    // Map a screen cell to what's drawn there. Rows past the end of the
+    // buffer map to its last line and columns past the end of a line to
+    // the line's end.
+    pub fn click_target(&self, column: u16, row: u16) -> ClickTarget {
+        let Point { y: height, .. } = self.get_dimensions();
+        let row = row as usize;
+        if row >= height {
+            return ClickTarget::StatusBar;
+        }
 
-        false
+        let y = (row + self.offset.y).min(self.content.len() - 1);
+        let line = &self.content[y];
+        let width = column as usize + self.offset.x;
+        let x = line
+            .x_at_width(width, self.settings.tab_width)
+            .unwrap_or(line.len());
+        ClickTarget::Text { x, y, width }
     }
 }
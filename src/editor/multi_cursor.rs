@@ -0,0 +1,194 @@
+use crate::editor::core::Editor;
+use crate::types::{Cursor, Point, Status};
+use std::mem;
+
+impl Editor {
+    pub fn primary_cursor(&self) -> Cursor {
+        Cursor {
+            position: self.cursor,
+            selection: self.selection,
+            preferred_width: self.preferred_width,
+        }
+    }
+
+    pub fn set_primary_cursor(&mut self, cursor: Cursor) {
+        self.cursor = cursor.position;
+        self.selection = cursor.selection;
+        self.preferred_width = cursor.preferred_width;
+    }
+
+    pub fn for_each_cursor(&mut self, mut edit: impl FnMut(&mut Editor)) {
+        self.for_each_cursor_indexed(|e, _| edit(e));
+    }
+
+    // Run `edit` once for every cursor, as a single undo step, along with
+    // the cursor's position in document order. Each run sees its cursor as
+    // the primary one while the others follow the edits.
+    pub fn for_each_cursor_indexed(&mut self, mut edit: impl FnMut(&mut Editor, usize)) {
+        if self.extra_cursors.is_empty() {
+            edit(self, 0);
+            return;
+        }
+
+        let mut cursors = mem::take(&mut self.extra_cursors);
+        cursors.insert(0, self.primary_cursor());
+
+        // Going from the bottom up keeps earlier edits out of the way
+        let mut order: Vec<usize> = (0..cursors.len()).collect();
+        order.sort_unstable_by_key(|i| std::cmp::Reverse(cursors[*i].position));
+
+        self.undo_stack.begin_group();
+        for (rank, i) in order.iter().copied().rev().enumerate().rev() {
+            self.set_primary_cursor(cursors[i]);
+            self.extra_cursors = cursors
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, c)| *c)
+                .collect();
+
+            edit(self, rank);
+
+            let mut others = mem::take(&mut self.extra_cursors).into_iter();
+            for (j, cursor) in cursors.iter_mut().enumerate() {
+                *cursor = if j == i {
+                    self.primary_cursor()
+                } else {
+                    others.next().unwrap_or(*cursor)
+                };
+            }
+        }
+        self.undo_stack.end_group();
+
+        self.set_primary_cursor(cursors.remove(0));
+        self.extra_cursors = cursors;
+        self.merge_cursors();
+        self.adjust_offset();
+    }
+
+    // Drop cursors that ended up in the same place as another one
+    fn merge_cursors(&mut self) {
+        let primary = self.cursor;
+        let mut seen = vec![primary];
+        self.extra_cursors.retain(|cursor| {
+            let duplicate = seen.contains(&cursor.position);
+            seen.push(cursor.position);
+            !duplicate
+        });
+    }
+
+    // Make `position` the primary cursor, keeping the current one as well
+    pub fn add_cursor(&mut self, position: Point, selection: Option<[Point; 2]>) {
+        let previous = self.primary_cursor();
+        self.extra_cursors.push(previous);
+        self.selection = selection;
+        self.move_cursor(position);
+        self.preferred_width =
+            self.content[position.y].width_to(position.x, self.settings.tab_width);
+        self.merge_cursors();
+    }
+
+    // Add a cursor on the line above the topmost cursor, or below the
+    // bottommost one, at the same display column
+    pub fn add_cursor_vertically(&mut self, up: bool) {
+        let positions = self
+            .extra_cursors
+            .iter()
+            .map(|c| c.position)
+            .chain([self.cursor]);
+        let edge = if up { positions.min() } else { positions.max() };
+        let Some(edge) = edge else {
+            return;
+        };
+
+        let y = match up {
+            true if edge.y > 0 => edge.y - 1,
+            false if edge.y + 1 < self.content.len() => edge.y + 1,
+            _ => return,
+        };
+
+        let tab_width = self.settings.tab_width;
+        let width = self.preferred_width;
+        let line = &self.content[y];
+        let x = line.x_at_width(width, tab_width).unwrap_or(line.len());
+
+        self.add_cursor(Point::new(x, y), None);
+        self.preferred_width = width;
+    }
+
+    // Select the word under the cursor, or add a cursor selecting the next
+    // occurrence of the selected text
+    pub fn add_cursor_at_next_match(&mut self) {
+        let Some(mut selection) = self.selection.filter(|[a, b]| a != b) else {
+            self.select_word();
+            return;
+        };
+        selection.sort_unstable();
+        let [a, b] = selection;
+        if a.y != b.y {
+            self.status = Some(Status::new(
+                "Can't search for text spanning lines".to_string(),
+            ));
+            return;
+        }
+
+        let needle = &self.content[a.y].graphemes[a.x..b.x];
+        let taken = |p: Point| {
+            p == a
+                || self
+                    .extra_cursors
+                    .iter()
+                    .any(|c| c.selection.is_some_and(|s| s.contains(&p)))
+        };
+
+        // Search forward from the selection, wrapping around the end
+        let count = self.content.len();
+        let found = (0..=count).find_map(|i| {
+            let y = (b.y + i) % count;
+            let graphemes = &self.content[y].graphemes;
+            let from = if i == 0 { b.x } else { 0 };
+            (from..=graphemes.len().saturating_sub(needle.len()))
+                .filter(|x| graphemes[*x..].starts_with(needle))
+                .map(|x| Point::new(x, y))
+                .find(|p| !taken(*p))
+        });
+
+        match found {
+            Some(start) => {
+                let end = Point::new(start.x + needle.len(), start.y);
+                self.add_cursor(end, Some([start, end]));
+            }
+            None => self.status = Some(Status::new("No more matches".to_string())),
+        }
+    }
+
+    // Select the word the cursor is in or next to
+    fn select_word(&mut self) {
+        let line = self.get_current_line();
+        let is_word = |x: usize| {
+            line.graphemes
+                .get(x)
+                .is_some_and(|g| g.chars().all(|c| c.is_alphanumeric() || c == '_'))
+        };
+
+        let mut start = self.cursor.x;
+        while start > 0 && is_word(start - 1) {
+            start -= 1;
+        }
+        let mut end = self.cursor.x;
+        while is_word(end) {
+            end += 1;
+        }
+
+        if start < end {
+            let y = self.cursor.y;
+            self.selection = Some([Point::new(start, y), Point::new(end, y)]);
+            self.move_cursor(Point::new(end, y));
+        }
+    }
+
+    pub fn clear_extra_cursors(&mut self) {
+        self.extra_cursors.clear();
+        self.selection = None;
+    }
+}
@@ -0,0 +1,177 @@
+use crate::action::{Action, ActionType};
+use crate::editor::core::Editor;
+use crate::types::{Line, Point};
+
+impl Editor {
+    fn pair_with_open(&self, c: char) -> Option<(char, char)> {
+        self.settings
+            .pairs()
+            .into_iter()
+            .find(|(open, _)| *open == c)
+    }
+
+    // Type a character, closing pairs, skipping over auto-inserted closers
+    // and wrapping the selection as needed
+    pub fn type_char(&mut self, c: char) {
+        let next = self.get_char_at(self.cursor);
+        let is_closer = self.settings.pairs().iter().any(|(_, close)| *close == c);
+
+        if is_closer
+            && next == Some(c.to_string())
+            && let Some(i) = self.auto_closed.iter().position(|p| *p == self.cursor)
+        {
+            self.auto_closed.remove(i);
+            self.move_cursor(Point::new(self.cursor.x + 1, self.cursor.y));
+            return;
+        }
+
+        let Some((open, close)) = self.pair_with_open(c) else {
+            self.replace_selection_with(c);
+            return;
+        };
+
+        if self.selection.is_some_and(|[a, b]| a != b) {
+            self.wrap_selection(open, close);
+            return;
+        }
+        self.selection = None;
+
+        // Only close before whitespace or a closer, and don't turn
+        // apostrophes in words into quote pairs
+        let previous = (self.cursor.x > 0)
+            .then(|| self.get_char_at(Point::new(self.cursor.x - 1, self.cursor.y)))
+            .flatten();
+        let before_word = previous.is_some_and(|g| g.chars().all(char::is_alphanumeric));
+        let after_ok = next.as_deref().is_none_or(|g| {
+            g.trim().is_empty()
+                || self
+                    .settings
+                    .pairs()
+                    .iter()
+                    .any(|(_, c)| c.to_string() == g)
+        });
+        if !after_ok || (open == close && before_word) {
+            self.insert_char(c);
+            return;
+        }
+
+        let start = self.cursor;
+        let redo = Action {
+            start,
+            end: None,
+            payload: Some(vec![Line {
+                graphemes: vec![open.to_string(), close.to_string()],
+            }]),
+            kind: ActionType::Insert,
+        };
+
+        let undo = Action {
+            start,
+            end: Some(Point::new(start.x + 2, start.y)),
+            payload: None,
+            kind: ActionType::Remove,
+        };
+
+        self.apply_action(&redo);
+        self.undo_stack.add(redo, undo);
+
+        let closer = Point::new(start.x + 1, start.y);
+        self.move_cursor(closer);
+        self.auto_closed.push(closer);
+    }
+
+    // Typing over a selection replaces it, as one undo step
+    fn replace_selection_with(&mut self, c: char) {
+        self.undo_stack.begin_group();
+        self.delete_selection();
+        self.insert_char(c);
+        self.undo_stack.end_group();
+    }
+
+    // Surround the selection with a pair, keeping the same text selected
+    fn wrap_selection(&mut self, open: char, close: char) {
+        let Some(mut selection) = self.selection else {
+            return;
+        };
+        selection.sort_unstable();
+        let [a, b] = selection;
+
+        let (mut redo, mut undo) = (vec![], vec![]);
+        self.replace_in_line(b.y, b.x..b.x, vec![close.to_string()], &mut redo, &mut undo);
+        self.replace_in_line(a.y, a.x..a.x, vec![open.to_string()], &mut redo, &mut undo);
+        undo.reverse();
+        self.undo_stack.add_group(redo, undo);
+
+        let shift = if a.y == b.y { 1 } else { 0 };
+        let (a, b) = (Point::new(a.x + 1, a.y), Point::new(b.x + shift, b.y));
+        let cursor_at_start = self.cursor == selection[0];
+        self.selection = Some(if cursor_at_start { [b, a] } else { [a, b] });
+        self.move_cursor(if cursor_at_start { a } else { b });
+    }
+
+    // Backspace between an empty pair removes both characters
+    pub fn remove_empty_pair(&mut self) -> bool {
+        if self.cursor.x == 0 {
+            return false;
+        }
+        let start = Point::new(self.cursor.x - 1, self.cursor.y);
+        let (Some(before), Some(after)) = (self.get_char_at(start), self.get_char_at(self.cursor))
+        else {
+            return false;
+        };
+        let is_pair = self
+            .settings
+            .pairs()
+            .iter()
+            .any(|(open, close)| open.to_string() == before && close.to_string() == after);
+        if !is_pair {
+            return false;
+        }
+
+        let redo = Action {
+            start,
+            end: Some(Point::new(start.x + 2, start.y)),
+            payload: None,
+            kind: ActionType::Remove,
+        };
+
+        let undo = Action {
+            start,
+            end: None,
+            payload: Some(vec![Line {
+                graphemes: vec![before, after],
+            }]),
+            kind: ActionType::Insert,
+        };
+
+        self.apply_action(&redo);
+        self.undo_stack.add(redo, undo);
+        true
+    }
+
+    // Keep auto-inserted closers pointing at the same characters. Edits
+    // spanning lines just forget them.
+    pub fn track_auto_closed(&mut self, action: &Action) {
+        let start = action.start;
+        match (action.kind, action.end, &action.payload) {
+            (ActionType::Insert, _, Some(payload)) if payload.len() == 1 => {
+                let len = payload[0].len();
+                for point in &mut self.auto_closed {
+                    if point.y == start.y && point.x >= start.x {
+                        point.x += len;
+                    }
+                }
+            }
+            (ActionType::Remove, Some(end), _) if end.y == start.y => {
+                self.auto_closed
+                    .retain(|p| p.y != start.y || p.x < start.x || p.x >= end.x);
+                for point in &mut self.auto_closed {
+                    if point.y == start.y && point.x >= end.x {
+                        point.x -= end.x - start.x;
+                    }
+                }
+            }
+            _ => self.auto_closed.clear(),
+        }
+    }
+}
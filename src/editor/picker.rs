@@ -0,0 +1,107 @@
+use crate::editor::core::Editor;
+use crate::types::line::{sanitize, truncate_to_width};
+use crate::types::{Point, Status};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::{cursor, execute};
+use std::io::{self, Write, stdout};
+use unicode_width::UnicodeWidthStr;
+
+// The picker never covers more than this many rows
+const MAX_ROWS: usize = 10;
+
+// One line summary of a history entry
+fn preview(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let first = lines
+        .iter()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+
+    let first = sanitize(first);
+    if lines.len() > 1 {
+        format!("{} (+{} lines)", first, lines.len() - 1)
+    } else {
+        first
+    }
+}
+
+impl Editor {
+    pub fn open_clipboard_picker(&mut self) {
+        self.import_clipboard();
+        if self.clipboard.is_empty() {
+            self.status = Some(Status::new("Clipboard history is empty".to_string()));
+            return;
+        }
+        self.picker = Some(0);
+    }
+
+    // Keys while the picker is open. Up and Down choose an entry, Enter or
+    // its number pastes it and Esc closes the picker.
+    pub fn handle_picker_key(&mut self, event: KeyEvent) {
+        let Some(selected) = self.picker else {
+            return;
+        };
+        let count = self.clipboard.len();
+        let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+
+        match event.code {
+            KeyCode::Up => self.picker = Some((selected + count - 1) % count),
+            KeyCode::Char('p') if ctrl => self.picker = Some((selected + count - 1) % count),
+            KeyCode::Down | KeyCode::Tab => self.picker = Some((selected + 1) % count),
+            KeyCode::Char('n') if ctrl => self.picker = Some((selected + 1) % count),
+            KeyCode::Enter => {
+                self.picker = None;
+                self.block = None;
+                self.paste_entry(selected);
+            }
+            KeyCode::Char(c @ '1'..='9') if !ctrl => {
+                let index = c as usize - '1' as usize;
+                if index < count.min(MAX_ROWS) {
+                    self.picker = None;
+                    self.block = None;
+                    self.paste_entry(index);
+                }
+            }
+            KeyCode::Esc => self.picker = None,
+            KeyCode::Char('c' | 'q') if ctrl => self.picker = None,
+            _ => (),
+        }
+    }
+
+    // Draw the picker over the bottom of the text area
+    pub fn draw_picker(&self) -> io::Result<()> {
+        let Some(selected) = self.picker else {
+            return Ok(());
+        };
+        let Point {
+            x: width,
+            y: height,
+        } = self.get_dimensions();
+
+        let rows = self.clipboard.len().min(MAX_ROWS).min(height);
+        if rows == 0 {
+            return Ok(());
+        }
+        // Scroll so the selected entry is always visible
+        let first = (selected + 1).saturating_sub(rows);
+
+        for (row, i) in (first..first + rows).enumerate() {
+            let text = format!(" {:>2}  {}", i + 1, preview(&self.clipboard.entries[i]));
+            let text = truncate_to_width(&text, width);
+            let padded = format!("{}{}", text, " ".repeat(width.saturating_sub(text.width())));
+
+            let style = if i == selected {
+                self.theme.selection
+            } else {
+                self.theme.status_bar
+            };
+            execute!(stdout(), cursor::MoveTo(0, (height - rows + row) as u16))?;
+            print!("{}", style.apply(padded));
+        }
+
+        let screen_y = height - rows + selected - first;
+        execute!(stdout(), cursor::MoveTo(0, screen_y as u16))?;
+        stdout().flush()
+    }
+}
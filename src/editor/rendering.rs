@@ -1,68 +1,213 @@
 use crate::editor::core::Editor;
-use crate::types::Point;
+use crate::types::line::{sanitize, truncate_to_width};
+use crate::types::{Marks, Point, Status};
 use crossterm::{
     cursor, execute,
-    style::Stylize,
+    style::{Color, Colors, SetColors},
     terminal::{self, ClearType},
 };
 use std::io::{self, Write, stdout};
-
-const STATUS_BAR_HEIGHT: usize = 1;
+use unicode_width::UnicodeWidthStr;
 
 impl Editor {
-    pub fn get_dimensions() -> Point {
-        let (width, height) = terminal::size().ok().unwrap();
-        // Always reserve space for status
-        Point::new(width as usize, height as usize - STATUS_BAR_HEIGHT)
+    pub fn get_dimensions(&self) -> Point {
+        // Without a terminal to ask, as in tests, assume the classic size
+        let (width, height) = terminal::size().unwrap_or((80, 24));
+        // Leave room for the status bar when it is shown
+        Point::new(
+            width as usize,
+            (height as usize).saturating_sub(self.config.status_bar_height()),
+        )
+    }
+
+    // Called after the terminal has been resized, before redrawing
+    pub fn handle_resize(&mut self) {
+        let Point { y: height, .. } = self.get_dimensions();
+
+        // Don't leave empty space below the last line if the window grew
+        self.offset.y = self
+            .offset
+            .y
+            .min(self.content.len().saturating_sub(height.max(1)));
+
+        if !self.scrolled {
+            self.adjust_offset();
+        }
+    }
+
+    // Graphemes and lines covered by every cursor's selection
+    fn selection_stats(&self) -> Option<(usize, usize)> {
+        let selections: Vec<[Point; 2]> = self
+            .selection
+            .into_iter()
+            .chain(self.extra_cursors.iter().filter_map(|c| c.selection))
+            .filter(|[a, b]| a != b)
+            .collect();
+        if selections.is_empty() {
+            return None;
+        }
+
+        let (mut chars, mut lines) = (0, 0);
+        for mut selection in selections {
+            selection.sort_unstable();
+            let [a, b] = selection;
+            // Line breaks count as one character each
+            chars += (a.y..=b.y)
+                .map(|y| self.content[y].len() + 1)
+                .sum::<usize>()
+                - a.x
+                - (self.content[b.y].len() + 1 - b.x);
+            lines += b.y - a.y + 1;
+        }
+        Some((chars, lines))
+    }
+
+    // Segments on the right of the status line with their priority. When
+    // space runs out, the lowest priority ones are dropped first.
+    fn status_segments(&self) -> Vec<(u8, String)> {
+        let mut segments = vec![];
+        if let Some((chars, lines)) = self.selection_stats() {
+            let lines = if lines == 1 {
+                String::new()
+            } else {
+                format!(", {} lines", lines)
+            };
+            segments.push((6, format!("{} selected{}", chars, lines)));
+        }
+        if !self.enhanced_keyboard {
+            segments.push((5, "legacy keys".to_string()));
+        }
+        segments.push((4, self.highlighter.language.name().to_string()));
+        let indent = if self.settings.insert_spaces {
+            "Spaces"
+        } else {
+            "Tabs"
+        };
+        segments.push((2, format!("{}: {}", indent, self.settings.indent_width())));
+        segments.push((1, self.format.charset.name().to_string()));
+        segments.push((0, self.format.line_ending.name().to_string()));
+        segments.push((3, format!("{} lines", self.content.len())));
+        segments
     }
 
     pub fn draw_status_line(&self) -> io::Result<()> {
         let Point {
             x: width,
             y: height,
-        } = Editor::get_dimensions();
+        } = self.get_dimensions();
 
-        let status = if self.status.is_some() && self.status.as_ref().unwrap().is_fresh() {
-            self.status.as_ref().unwrap().text.clone()
+        let status = if let Some(status) = self
+            .status
+            .as_ref()
+            .filter(|s| s.is_fresh(self.config.status_timeout()))
+        {
+            // Messages may contain control characters too
+            truncate_to_width(&sanitize(&status.text), width)
         } else {
-            format!(
-                " {} • {}:{} ",
-                self.filename.as_deref().unwrap_or("[No Name]"),
+            let column = self
+                .get_current_line()
+                .width_to(self.cursor.x, self.settings.tab_width);
+            let name = sanitize(self.filename.as_deref().unwrap_or("[No Name]"));
+            let position = format!(
+                "{} • {}:{} ",
+                if self.undo_stack.is_modified() {
+                    " [+]"
+                } else {
+                    ""
+                },
                 self.cursor.y + 1,
-                self.cursor.x + 1,
-            )
-        };
+                column + 1,
+            );
+            let left = format!(" {}{}", name, position);
 
-        // Truncate if too long
-        let truncated_status = if status.len() > width as usize {
-            format!("{}...", &status[..width as usize - 3])
-        } else {
-            status
+            let mut segments = self.status_segments();
+            let right_text = |segments: &[(u8, String)]| {
+                let texts: Vec<&str> = segments.iter().map(|(_, text)| text.as_str()).collect();
+                format!("{} ", texts.join(" • "))
+            };
+            while !segments.is_empty() && left.width() + right_text(&segments).width() > width {
+                let lowest = (0..segments.len())
+                    .min_by_key(|&i| segments[i].0)
+                    .unwrap_or(0);
+                segments.remove(lowest);
+            }
+
+            if segments.is_empty() {
+                // Shorten the file name before the position
+                let room = width.saturating_sub(position.width() + 1);
+                if room > 1 {
+                    format!(" {}{}", truncate_to_width(&name, room), position)
+                } else {
+                    truncate_to_width(&left, width)
+                }
+            } else {
+                let right = right_text(&segments);
+                let gap = width - left.width() - right.width();
+                format!("{}{}{}", left, " ".repeat(gap), right)
+            }
         };
 
+        // Fill the whole row so the bar has a consistent background
+        let padding = " ".repeat(width.saturating_sub(status.width()));
+
         // Move to status line and draw
         execute!(stdout(), cursor::MoveTo(0, height as u16))?;
-        print!("{}", truncated_status.on_dark_grey());
+        print!("{}", self.theme.status_bar.apply(status + &padding));
 
         stdout().flush()?;
         Ok(())
     }
 
+    pub fn toggle_whitespace(&mut self) {
+        self.settings.show_whitespace = !self.settings.show_whitespace;
+        let state = if self.settings.show_whitespace {
+            "shown"
+        } else {
+            "hidden"
+        };
+        self.status = Some(Status::new(format!("Whitespace {}", state)));
+    }
+
+    pub fn toggle_indent_guides(&mut self) {
+        self.settings.indent_guides = !self.settings.indent_guides;
+        let state = if self.settings.indent_guides {
+            "shown"
+        } else {
+            "hidden"
+        };
+        self.status = Some(Status::new(format!("Indent guides {}", state)));
+    }
+
+    // Set the theme's base colors so that cleared areas get its background
+    fn set_base_colors(&self) -> io::Result<()> {
+        let colors = Colors {
+            foreground: Some(self.theme.text.fg.unwrap_or(Color::Reset)),
+            background: Some(self.theme.text.bg.unwrap_or(Color::Reset)),
+        };
+        execute!(stdout(), SetColors(colors))
+    }
+
     pub fn draw(&mut self) -> io::Result<()> {
+        self.set_base_colors()?;
         execute!(stdout(), terminal::Clear(ClearType::All))?;
         let Point {
             x: width,
             y: height,
-        } = Editor::get_dimensions();
+        } = self.get_dimensions();
 
-        let selection = self.selection.map_or(None, |selection| {
-            let [a, b] = selection;
-            if b.y > a.y || b.y == a.y && b.x > a.x {
-                Some([a, b])
-            } else {
-                Some([b, a])
-            }
-        });
+        let selections: Vec<[Point; 2]> = self
+            .selection
+            .into_iter()
+            .chain(self.extra_cursors.iter().filter_map(|c| c.selection))
+            .map(|mut selection| {
+                selection.sort_unstable();
+                selection
+            })
+            .collect();
+
+        self.highlighter
+            .update(&self.content, self.offset.y + height);
+        let brackets = self.matching_brackets();
 
         // Draw content
         for (i, line) in self
@@ -73,56 +218,78 @@ impl Editor {
             .take(height)
         {
             execute!(stdout(), cursor::MoveTo(0, i as u16 - self.offset.y as u16))?;
+            self.set_base_colors()?;
             execute!(stdout(), terminal::Clear(ClearType::CurrentLine))?;
 
-            let active = self.cursor.y == i;
-            let offset = if active { Some(self.offset.x) } else { None };
-
-            // Map selection [Point, Point] to [usize, usize] corresponding to x-indices
-            // on this line
-            let highlight = selection.map_or(None, |selection| match selection {
-                // Single line selection
-                [Point { x: x1, y: y1 }, Point { x: x2, y: y2 }] if y1 == i && y2 == i => {
-                    Some([x1, x2])
-                }
-                // Selection starts on this row and continues past line break
-                [Point { x: x1, y: y1 }, Point { x: _, y: y2 }] if y1 == i && y2 != i => {
-                    Some([x1, line.len()])
-                }
-                // Selection started earlier and ends on this line
-                [Point { x: _, y: y1 }, Point { x: x2, y: y2 }] if y1 != i && y2 == i => {
-                    Some([0, x2])
-                }
-                // Selection wholly encompasses this line
-                [Point { x: _, y: y1 }, Point { x: _, y: y2 }] if y1 < i && y2 > i => {
-                    Some([0, line.len()])
-                }
-                _ => None,
-            });
+            let marks = Marks {
+                selections: selections
+                    .iter()
+                    .filter_map(|selection| selection_on_line(*selection, i, line.len()))
+                    .collect(),
+                brackets: brackets
+                    .iter()
+                    .flatten()
+                    .filter(|p| p.y == i)
+                    .map(|p| p.x)
+                    .collect(),
+                cursors: self
+                    .extra_cursors
+                    .iter()
+                    .filter(|c| c.position.y == i)
+                    .map(|c| c.position.x)
+                    .collect(),
+            };
 
-            line.print(offset, highlight);
+            line.print(
+                self.offset.x..self.offset.x + width,
+                &marks,
+                self.highlighter.tokens(i),
+                &self.theme,
+                &self.settings,
+            );
         }
 
-        // Draw status line
-        self.draw_status_line()?;
+        // Draw status line. Without one there's no row reserved for it.
+        if self.config.status_bar {
+            self.draw_status_line()?;
+        }
 
         // Position cursor correctly
-        let screen_y = self.cursor.y.saturating_sub(self.offset.y);
+        let screen_y = self.cursor.y.wrapping_sub(self.offset.y);
         let line = self.get_current_line();
-        let mut display_x: usize = line
-            .width_to(self.cursor.x)
-            .saturating_sub(line.width_to(self.offset.x));
+        let column = line.width_to(self.cursor.x, self.settings.tab_width);
+        let display_x = column.wrapping_sub(self.offset.x);
 
-        if line.len() > self.cursor.x {
-            display_x = display_x.min(width - 1);
+        // Only show the cursor if it's within the editor area, which it may
+        // not be after scrolling with the mouse
+        if screen_y < height && display_x < width {
+            execute!(
+                stdout(),
+                cursor::MoveTo(display_x as u16, screen_y as u16),
+                cursor::Show
+            )?;
+        } else {
+            execute!(stdout(), cursor::Hide)?;
         }
 
-        // Only position cursor if it's within the editor area
-        if screen_y < height {
-            execute!(stdout(), cursor::MoveTo(display_x as u16, screen_y as u16))?;
-        }
+        self.draw_picker()?;
 
         stdout().flush()?;
         Ok(())
     }
 }
+
+// Map a selection to the x-indices it covers on line `y`
+fn selection_on_line([a, b]: [Point; 2], y: usize, len: usize) -> Option<[usize; 2]> {
+    match (a.y == y, b.y == y) {
+        // Single line selection
+        (true, true) => Some([a.x, b.x]),
+        // Selection starts on this row and continues past line break
+        (true, false) => Some([a.x, len]),
+        // Selection started earlier and ends on this line
+        (false, true) => Some([0, b.x]),
+        // Selection wholly encompasses this line
+        (false, false) if a.y < y && b.y > y => Some([0, len]),
+        _ => None,
+    }
+}
@@ -11,43 +11,135 @@ impl Editor {
         self.selection = Some(selection);
     }
 
-    pub fn copy(&mut self) {
-        if let Some(mut selection) = self.selection {
-            selection.sort_unstable();
-            let [a, b] = selection;
-            let mut lines = vec![];
-
-            for (i, line) in self
-                .content
-                .iter()
-                .skip(a.y)
-                .take(b.y + 1 - a.y)
-                .enumerate()
-            {
-                if a.y == b.y {
-                    // Single line
-                    lines.push(Line {
-                        graphemes: line.graphemes[a.x..b.x].to_vec(),
-                    });
-                } else if i == 0 {
-                    // First line in multiline
-                    lines.push(Line {
-                        graphemes: line.graphemes[a.x..].to_vec(),
-                    });
-                } else if i == b.y - a.y {
-                    // Last line in multiline
-                    lines.push(Line {
-                        graphemes: line.graphemes[..b.x].to_vec(),
-                    });
+    pub fn select_all(&mut self) {
+        let y_max = self.content.len() - 1;
+        let end = Point::new(self.content[y_max].len(), y_max);
+        self.selection = Some([Point::zero(), end]);
+        self.move_cursor(end);
+    }
+
+    // Select the current line, or extend the selection by a line if it
+    // already covers whole lines
+    pub fn select_line(&mut self) {
+        let (first, last) = match self.selection {
+            Some(mut selection) => {
+                selection.sort_unstable();
+                let [a, b] = selection;
+                // A selection ending at the start of a line already covers
+                // the line before it, unless it's already made of whole lines
+                let whole_lines = a.x == 0 && b.x == 0 && b.y > a.y;
+                let last = if b.x == 0 && b.y > a.y && !whole_lines {
+                    b.y
                 } else {
-                    // Middle line
-                    lines.push(Line {
-                        graphemes: line.graphemes.to_vec(),
-                    });
-                }
+                    b.y + 1
+                };
+                (a.y, last)
             }
+            None => (self.cursor.y, self.cursor.y + 1),
+        };
 
-            self.clipboard = Some(lines);
+        let end = if last < self.content.len() {
+            Point::new(0, last)
+        } else {
+            let y_max = self.content.len() - 1;
+            Point::new(self.content[y_max].len(), y_max)
+        };
+
+        self.selection = Some([Point::new(0, first), end]);
+        self.move_cursor(end);
+    }
+
+    // The text between two points, as lines
+    pub fn text_between(&self, a: Point, b: Point) -> Vec<Line> {
+        let mut lines = vec![];
+
+        for (i, line) in self
+            .content
+            .iter()
+            .skip(a.y)
+            .take(b.y + 1 - a.y)
+            .enumerate()
+        {
+            if a.y == b.y {
+                // Single line
+                lines.push(Line {
+                    graphemes: line.graphemes[a.x..b.x].to_vec(),
+                });
+            } else if i == 0 {
+                // First line in multiline
+                lines.push(Line {
+                    graphemes: line.graphemes[a.x..].to_vec(),
+                });
+            } else if i == b.y - a.y {
+                // Last line in multiline
+                lines.push(Line {
+                    graphemes: line.graphemes[..b.x].to_vec(),
+                });
+            } else {
+                // Middle line
+                lines.push(Line {
+                    graphemes: line.graphemes.to_vec(),
+                });
+            }
         }
+
+        lines
+    }
+
+    // Remove the selected text, returning whether there was any
+    pub fn delete_selection(&mut self) -> bool {
+        let Some(mut selection) = self.selection.take() else {
+            return false;
+        };
+        selection.sort_unstable();
+        let [a, b] = selection;
+        if a == b {
+            return false;
+        }
+
+        let (mut redo, mut undo) = (vec![], vec![]);
+        self.remove_text(a, b, &mut redo, &mut undo);
+        self.undo_stack.add_group(redo, undo);
+        self.move_cursor(a);
+        true
+    }
+
+    // Copy every cursor's selection, one after another on separate lines.
+    // With several cursors, one without a selection copies an empty line so
+    // that pasting gives each cursor its own line again.
+    pub fn copy(&mut self) {
+        let mut cursors: Vec<(Point, Option<[Point; 2]>)> = self
+            .extra_cursors
+            .iter()
+            .map(|c| (c.position, c.selection))
+            .chain([(self.cursor, self.selection)])
+            .map(|(position, selection)| {
+                let selection = selection.map(|mut selection| {
+                    selection.sort_unstable();
+                    selection
+                });
+                (selection.map_or(position, |[a, _]| a), selection)
+            })
+            .collect();
+        if cursors.iter().all(|(_, selection)| selection.is_none()) {
+            return;
+        }
+        cursors.sort_unstable();
+
+        let mut lines: Vec<Line> = vec![];
+        for (_, selection) in cursors {
+            match selection {
+                Some([a, b]) => lines.extend(self.text_between(a, b)),
+                None => lines.push(Line::new()),
+            }
+        }
+        self.push_clipboard(&lines);
+    }
+
+    pub fn cut(&mut self) {
+        self.copy();
+        self.for_each_cursor(|e| {
+            e.delete_selection();
+        });
     }
 }
@@ -0,0 +1,128 @@
+use crate::editor::core::Editor;
+use crossterm::{
+    cursor,
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
+    style::ResetColor,
+    terminal,
+};
+use std::env;
+use std::fs;
+use std::io::{self, stdout};
+use std::panic;
+use std::path::PathBuf;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Whether the terminal currently needs restoring
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+// Whether keyboard enhancement flags were pushed and need popping
+static ENHANCED: AtomicBool = AtomicBool::new(false);
+
+// Puts the terminal into the state the editor needs and restores it when
+// dropped, including when returning early with an error
+pub struct TerminalGuard {
+    // The terminal supports the kitty keyboard protocol
+    pub enhanced_keyboard: bool,
+}
+
+impl TerminalGuard {
+    pub fn enter() -> io::Result<Self> {
+        install_panic_hook();
+
+        terminal::enable_raw_mode()?;
+        // From here on, dropping the guard undoes whatever succeeded
+        ACTIVE.store(true, Ordering::SeqCst);
+        // Linux console, tmux and screen usually don't support it, and a
+        // failed query counts as no support
+        let enhanced_keyboard = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        let guard = TerminalGuard { enhanced_keyboard };
+
+        let mut stdout = stdout();
+        execute!(stdout, terminal::EnterAlternateScreen)?;
+        if enhanced_keyboard {
+            execute!(
+                stdout,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+            )?;
+            ENHANCED.store(true, Ordering::SeqCst);
+        }
+        execute!(stdout, EnableMouseCapture)?;
+        execute!(stdout, EnableBracketedPaste)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// Return the terminal to normal, once. Errors are ignored so that every
+// step is tried.
+pub fn restore() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let mut stdout = stdout();
+    let _ = execute!(stdout, ResetColor, cursor::Show);
+    let _ = execute!(stdout, DisableBracketedPaste);
+    let _ = execute!(stdout, DisableMouseCapture);
+    if ENHANCED.swap(false, Ordering::SeqCst) {
+        let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+    }
+    let _ = execute!(stdout, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
+
+// Restore the terminal before the panic message is printed, or it would be
+// lost on the alternate screen
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            default_hook(info);
+        }));
+    });
+}
+
+impl Editor {
+    // Where an emergency save goes: next to the file, or in the temporary
+    // directory for unnamed buffers
+    fn recovery_paths(&self) -> Vec<PathBuf> {
+        let mut paths = vec![];
+        if let Some(filename) = &self.filename {
+            paths.push(PathBuf::from(format!("{}.fedit-recovery", filename)));
+        }
+        paths.push(env::temp_dir().join(format!("fedit-recovery-{}.txt", std::process::id())));
+        paths
+    }
+
+    // Write the buffer somewhere safe after a crash. Nothing is written if
+    // there are no unsaved changes.
+    pub fn emergency_save(&self) -> Option<PathBuf> {
+        if !self.undo_stack.is_modified() {
+            return None;
+        }
+
+        let lines: Vec<String> = self
+            .content
+            .iter()
+            .map(|line| line.graphemes.concat())
+            .collect();
+        let bytes = self
+            .format
+            .encode(&lines)
+            .unwrap_or_else(|_| lines.join("\n").into_bytes());
+
+        self.recovery_paths()
+            .into_iter()
+            .find(|path| fs::write(path, &bytes).is_ok())
+    }
+}
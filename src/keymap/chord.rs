@@ -0,0 +1,145 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+
+// A single key press together with its modifiers, e.g. Ctrl+K
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("enter", KeyCode::Enter),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("esc", KeyCode::Esc),
+    ("space", KeyCode::Char(' ')),
+];
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }.normalize()
+    }
+
+    pub fn from_event(event: &KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+
+    // Terminals report Shift in different ways. Letters are stored lowercase
+    // with an explicit Shift, other characters carry the shift in the char
+    // itself, and Shift+Tab is always BackTab.
+    fn normalize(mut self) -> Self {
+        match self.code {
+            KeyCode::Char(c) if c.is_uppercase() => {
+                self.code = KeyCode::Char(c.to_lowercase().next().unwrap_or(c));
+                self.modifiers |= KeyModifiers::SHIFT;
+            }
+            KeyCode::Char(c) if !c.is_alphabetic() && c != ' ' => {
+                self.modifiers -= KeyModifiers::SHIFT;
+            }
+            KeyCode::Tab if self.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.code = KeyCode::BackTab;
+                self.modifiers -= KeyModifiers::SHIFT;
+            }
+            KeyCode::BackTab => self.modifiers -= KeyModifiers::SHIFT,
+            _ => (),
+        }
+        self
+    }
+
+    // Parse a chord such as `ctrl+shift+up` or `alt+/`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let lowercase = text.to_lowercase();
+        // `+` itself can be a key, as in `ctrl++`
+        let (modifiers, key) = match lowercase.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None => match lowercase.rsplit_once('+') {
+                Some((modifiers, key)) => (modifiers, key),
+                None => ("", lowercase.as_str()),
+            },
+        };
+
+        let mut chord_modifiers = KeyModifiers::NONE;
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            chord_modifiers |= match modifier {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "option" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                "super" | "cmd" | "win" => KeyModifiers::SUPER,
+                _ => return Err(format!("unknown modifier '{}' in '{}'", modifier, text)),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                if let Some(n) = key.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    KeyCode::F(n)
+                } else {
+                    NAMED_KEYS
+                        .iter()
+                        .find(|(name, _)| *name == key)
+                        .map(|(_, code)| *code)
+                        .ok_or_else(|| format!("unknown key '{}' in '{}'", key, text))?
+                }
+            }
+        };
+
+        Ok(Self::new(code, chord_modifiers))
+    }
+
+    // Parse a space separated sequence such as `ctrl+k ctrl+c`
+    pub fn parse_sequence(text: &str) -> Result<Vec<Self>, String> {
+        let sequence = text
+            .split_whitespace()
+            .map(Self::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if sequence.is_empty() {
+            return Err("empty key binding".to_string());
+        }
+        Ok(sequence)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl"),
+            (KeyModifiers::ALT, "Alt"),
+            (KeyModifiers::SHIFT, "Shift"),
+            (KeyModifiers::SUPER, "Super"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_uppercase()),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            code => match NAMED_KEYS.iter().find(|(_, named)| *named == code) {
+                Some((name, _)) => {
+                    let mut chars = name.chars();
+                    let first = chars.next().unwrap().to_uppercase();
+                    write!(f, "{}{}", first, chars.as_str())
+                }
+                None => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
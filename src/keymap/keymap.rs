@@ -0,0 +1,170 @@
+use super::KeyChord;
+use crate::editor::commands::find_command;
+use std::collections::HashMap;
+
+const DEFAULT_BINDINGS: &[(&str, &str)] = &[
+    ("ctrl+q", "quit"),
+    ("ctrl+s", "save"),
+    ("ctrl+c", "copy"),
+    ("ctrl+x", "cut"),
+    ("ctrl+v", "paste"),
+    ("ctrl+shift+v", "paste.history"),
+    ("alt+v", "paste.cycle"),
+    ("ctrl+z", "undo"),
+    ("ctrl+y", "redo"),
+    ("ctrl+a", "select.all"),
+    ("ctrl+l", "select.line"),
+    ("alt+w", "view.toggle_whitespace"),
+    ("alt+i", "view.toggle_indent_guides"),
+    ("backspace", "edit.backspace"),
+    ("tab", "edit.tab"),
+    ("shift+tab", "edit.outdent"),
+    ("ctrl+shift+d", "line.duplicate"),
+    ("alt+up", "line.move_up"),
+    ("alt+down", "line.move_down"),
+    ("ctrl+shift+k", "line.delete"),
+    ("ctrl+j", "line.join"),
+    ("ctrl+/", "edit.toggle_comment"),
+    ("alt+shift+a", "edit.toggle_block_comment"),
+    ("alt+shift+up", "cursor.add_above"),
+    ("alt+shift+down", "cursor.add_below"),
+    ("ctrl+alt+shift+up", "select.block_up"),
+    ("ctrl+alt+shift+down", "select.block_down"),
+    ("ctrl+alt+shift+left", "select.block_left"),
+    ("ctrl+alt+shift+right", "select.block_right"),
+    ("ctrl+d", "cursor.add_next_match"),
+    ("esc", "cursor.clear"),
+    ("alt+m", "move.matching_bracket"),
+    ("alt+shift+m", "select.inside_brackets"),
+    ("enter", "edit.newline"),
+    ("up", "move.up"),
+    ("down", "move.down"),
+    ("left", "move.left"),
+    ("right", "move.right"),
+    ("shift+up", "select.up"),
+    ("shift+down", "select.down"),
+    ("shift+left", "select.left"),
+    ("shift+right", "select.right"),
+    ("super+up", "move.file_start"),
+    ("super+down", "move.file_end"),
+    ("super+left", "move.line_start"),
+    ("super+right", "move.line_end"),
+    ("super+shift+up", "select.file_start"),
+    ("super+shift+down", "select.file_end"),
+    ("super+shift+left", "select.line_start"),
+    ("super+shift+right", "select.line_end"),
+    ("home", "move.line_start"),
+    ("end", "move.line_end"),
+    ("shift+home", "select.line_start"),
+    ("shift+end", "select.line_end"),
+    ("ctrl+home", "move.file_start"),
+    ("ctrl+end", "move.file_end"),
+    ("ctrl+shift+home", "select.file_start"),
+    ("ctrl+shift+end", "select.file_end"),
+    ("alt+left", "move.word_left"),
+    ("alt+right", "move.word_right"),
+    ("ctrl+left", "move.word_left"),
+    ("ctrl+right", "move.word_right"),
+    ("alt+shift+left", "select.word_left"),
+    ("alt+shift+right", "select.word_right"),
+    ("ctrl+shift+left", "select.word_left"),
+    ("ctrl+shift+right", "select.word_right"),
+];
+
+// Added when the terminal doesn't speak the kitty keyboard protocol. Without
+// it Ctrl+Shift+letter arrives as Ctrl+letter, Super isn't reported at all
+// and Ctrl+/ is sent as Ctrl+7.
+const LEGACY_BINDINGS: &[(&str, &str)] = &[
+    ("ctrl+7", "edit.toggle_comment"),
+    ("alt+shift+d", "line.duplicate"),
+    ("alt+shift+k", "line.delete"),
+    ("alt+shift+v", "paste.history"),
+];
+
+// Bindings may also be set to this to remove a default binding
+const UNBOUND: &str = "none";
+
+pub enum Binding<'a> {
+    Command(&'a str),
+    // The keys so far are the start of a longer sequence
+    Prefix,
+    None,
+}
+
+pub struct Keymap {
+    bindings: HashMap<Vec<KeyChord>, String>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Self {
+            bindings: HashMap::new(),
+        };
+        for (keys, command) in DEFAULT_BINDINGS {
+            keymap
+                .bind(keys, command)
+                .expect("Invalid default key binding");
+        }
+        keymap
+    }
+}
+
+impl Keymap {
+    // The default bindings with the user's `[keys]` table applied on top
+    pub fn with_overrides(overrides: &HashMap<String, String>) -> Result<Self, String> {
+        Self::for_keyboard(overrides, true)
+    }
+
+    // Like `with_overrides`, adding alternate bindings for keys that legacy
+    // terminals can't send when `enhanced` is false
+    pub fn for_keyboard(
+        overrides: &HashMap<String, String>,
+        enhanced: bool,
+    ) -> Result<Self, String> {
+        let mut keymap = Self::default();
+        if !enhanced {
+            for (keys, command) in LEGACY_BINDINGS {
+                keymap
+                    .bind(keys, command)
+                    .expect("Invalid legacy key binding");
+            }
+        }
+        for (keys, command) in overrides {
+            keymap.bind(keys, command)?;
+        }
+        Ok(keymap)
+    }
+
+    pub fn bind(&mut self, keys: &str, command: &str) -> Result<(), String> {
+        let sequence = KeyChord::parse_sequence(keys)?;
+
+        if command == UNBOUND {
+            self.bindings.remove(&sequence);
+            return Ok(());
+        }
+
+        if find_command(command).is_none() {
+            return Err(format!("unknown command '{}' bound to '{}'", command, keys));
+        }
+
+        self.bindings.insert(sequence, command.to_string());
+        Ok(())
+    }
+
+    pub fn lookup(&self, keys: &[KeyChord]) -> Binding<'_> {
+        if let Some(command) = self.bindings.get(keys) {
+            return Binding::Command(command);
+        }
+
+        let prefix = self
+            .bindings
+            .keys()
+            .any(|sequence| sequence.len() > keys.len() && sequence.starts_with(keys));
+
+        if prefix {
+            Binding::Prefix
+        } else {
+            Binding::None
+        }
+    }
+}
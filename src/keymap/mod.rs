@@ -0,0 +1,6 @@
+pub mod chord;
+#[allow(clippy::module_inception)]
+pub mod keymap;
+
+pub use chord::KeyChord;
+pub use keymap::{Binding, Keymap};
@@ -1,5 +1,10 @@
 pub mod action;
+pub mod clipboard;
+pub mod config;
 pub mod editor;
+pub mod keymap;
+pub mod syntax;
+pub mod theme;
 pub mod types;
 
 // Re-export commonly used items
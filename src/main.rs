@@ -1,18 +1,52 @@
+use fedit::config::Config;
 use fedit::editor::Editor;
+use fedit::theme::Theme;
 
 use clap::Parser;
 use std::io;
+use std::path::PathBuf;
+use std::process;
 
 #[derive(Parser)]
 #[command(name = "fedit")]
 #[command(about = "A simple text editor")]
 struct Cli {
     file: Option<String>,
+    /// Color theme, either built-in or from ~/.config/fedit/themes
+    #[arg(long)]
+    theme: Option<String>,
+    /// Config file to use instead of ~/.config/fedit/config.toml
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// List available themes and exit
+    #[arg(long)]
+    list_themes: bool,
+}
+
+// Report a startup error, such as an invalid config file, and exit
+fn fail(message: String) -> ! {
+    eprintln!("fedit: {}", message);
+    process::exit(1)
 }
 
 fn main() -> io::Result<()> {
     let cli = Cli::parse();
-    let mut editor = Editor::new();
+
+    if cli.list_themes {
+        for name in Theme::available() {
+            println!("{}", name);
+        }
+        return Ok(());
+    }
+
+    let mut config = Config::load(cli.config.as_deref()).unwrap_or_else(|e| fail(e));
+    if let Some(theme) = cli.theme {
+        config.theme = theme;
+    }
+
+    let theme = Theme::load(&config.theme).unwrap_or_else(|e| fail(e));
+    let mut editor = Editor::with_config(config);
+    editor.theme = theme;
 
     if let Some(filename) = cli.file {
         editor.load_file(&filename)?;
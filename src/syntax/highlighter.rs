@@ -0,0 +1,70 @@
+use super::{Language, State, TokenKind};
+use crate::types::Line;
+
+struct HighlightedLine {
+    start: State,
+    end: State,
+    tokens: Vec<TokenKind>,
+}
+
+// Caches tokens per line so that only lines touched by an edit, and the
+// lines after them whose starting state changed, are tokenized again.
+pub struct Highlighter {
+    pub language: Language,
+    lines: Vec<Option<HighlightedLine>>,
+    // Every line before this index is highlighted and up to date
+    valid_until: usize,
+}
+
+impl Highlighter {
+    pub fn new(language: Language, line_count: usize) -> Self {
+        Self {
+            language,
+            lines: (0..line_count).map(|_| None).collect(),
+            valid_until: 0,
+        }
+    }
+
+    // Record that `removed` lines starting at `y` were replaced by `inserted` lines
+    pub fn edit(&mut self, y: usize, removed: usize, inserted: usize) {
+        let end = (y + removed).min(self.lines.len());
+        self.lines.splice(y..end, (0..inserted).map(|_| None));
+        self.valid_until = self.valid_until.min(y);
+    }
+
+    // Bring every line before `until` up to date
+    pub fn update(&mut self, content: &[Line], until: usize) {
+        let until = until.min(content.len());
+        let mut y = self.valid_until;
+
+        while y < until {
+            let start = self.start_state(y);
+            let fresh = self.lines[y]
+                .as_ref()
+                .is_some_and(|line| line.start == start);
+
+            if !fresh {
+                let (tokens, end) = self.language.tokenize(&content[y], start);
+                self.lines[y] = Some(HighlightedLine { start, end, tokens });
+            }
+
+            y += 1;
+        }
+
+        self.valid_until = self.valid_until.max(y);
+    }
+
+    pub fn tokens(&self, y: usize) -> &[TokenKind] {
+        match self.lines.get(y) {
+            Some(Some(line)) if y < self.valid_until => &line.tokens,
+            _ => &[],
+        }
+    }
+
+    fn start_state(&self, y: usize) -> State {
+        match y.checked_sub(1).and_then(|y| self.lines[y].as_ref()) {
+            Some(previous) => previous.end,
+            None => State::Normal,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use super::scanner::Scanner;
+use super::{State, TokenKind};
+use crate::types::Line;
+
+pub fn tokenize(line: &Line, _state: State) -> (Vec<TokenKind>, State) {
+    let mut s = Scanner::new(line);
+
+    while let Some(c) = s.peek() {
+        if c == '"' {
+            string(&mut s);
+        } else if c == '-' || c.is_ascii_digit() {
+            let len = 1 + s.chars[s.pos + 1..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+                .count();
+            s.mark(len, TokenKind::Number);
+        } else if c.is_alphabetic() {
+            let len = s.word_len();
+            let kind = match s.word(len).as_str() {
+                "true" | "false" | "null" => TokenKind::Constant,
+                _ => TokenKind::Text,
+            };
+            s.mark(len, kind);
+        } else if c.is_ascii_punctuation() {
+            s.mark(1, TokenKind::Punctuation);
+        } else {
+            s.mark(1, TokenKind::Text);
+        }
+    }
+
+    // JSON strings can't span lines so there is never any state to carry over
+    (s.finish(), State::Normal)
+}
+
+// Object keys are strings followed by a colon
+fn string(s: &mut Scanner) {
+    let start = s.pos;
+    s.mark(1, TokenKind::String);
+    s.string_body('"', true);
+
+    let key = s.chars[s.pos..]
+        .iter()
+        .find(|c| !c.is_whitespace())
+        .is_some_and(|c| *c == ':');
+
+    if key {
+        let end = s.pos;
+        s.pos = start;
+        s.mark(end - start, TokenKind::Property);
+    }
+}
@@ -0,0 +1,105 @@
+use super::{State, TokenKind, json, markdown, rust, shell, toml};
+use crate::types::Line;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    PlainText,
+    Rust,
+    Toml,
+    Markdown,
+    Json,
+    Shell,
+}
+
+impl Language {
+    pub const ALL: [Language; 6] = [
+        Language::PlainText,
+        Language::Rust,
+        Language::Toml,
+        Language::Markdown,
+        Language::Json,
+        Language::Shell,
+    ];
+
+    pub fn from_filename(filename: &str) -> Self {
+        let path = Path::new(filename);
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+
+        match name {
+            "Cargo.lock" => return Language::Toml,
+            ".bashrc" | ".bash_profile" | ".profile" | ".zshrc" => return Language::Shell,
+            _ => (),
+        }
+
+        match extension.to_lowercase().as_str() {
+            "rs" => Language::Rust,
+            "toml" => Language::Toml,
+            "md" | "markdown" => Language::Markdown,
+            "json" => Language::Json,
+            "sh" | "bash" | "zsh" => Language::Shell,
+            _ => Language::PlainText,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::PlainText => "text",
+            Language::Rust => "rust",
+            Language::Toml => "toml",
+            Language::Markdown => "markdown",
+            Language::Json => "json",
+            Language::Shell => "shell",
+        }
+    }
+
+    // Line endings that start an indented block
+    pub fn indent_after(&self) -> &'static str {
+        match self {
+            Language::PlainText | Language::Markdown => "",
+            Language::Rust => "{[(",
+            Language::Toml => "[",
+            Language::Json => "{[",
+            Language::Shell => "{(",
+        }
+    }
+
+    // Characters that are closed automatically, as open/close pairs
+    pub fn pairs(&self) -> &'static str {
+        match self {
+            // Single quotes are lifetimes and apostrophes more often than not
+            Language::PlainText | Language::Rust | Language::Json => "()[]{}\"\"",
+            Language::Markdown => "()[]{}\"\"``",
+            Language::Toml | Language::Shell => "()[]{}\"\"''",
+        }
+    }
+
+    pub fn line_comment(&self) -> &'static str {
+        match self {
+            Language::Rust => "//",
+            Language::Toml | Language::Shell => "#",
+            Language::PlainText | Language::Markdown | Language::Json => "",
+        }
+    }
+
+    pub fn block_comment(&self) -> (&'static str, &'static str) {
+        match self {
+            Language::Rust => ("/*", "*/"),
+            Language::Markdown => ("<!--", "-->"),
+            _ => ("", ""),
+        }
+    }
+
+    // Tokenize a single line given the state left behind by the previous one
+    pub fn tokenize(&self, line: &Line, state: State) -> (Vec<TokenKind>, State) {
+        match self {
+            Language::PlainText => (vec![TokenKind::Text; line.len()], State::Normal),
+            Language::Rust => rust::tokenize(line, state),
+            Language::Toml => toml::tokenize(line, state),
+            Language::Markdown => markdown::tokenize(line, state),
+            Language::Json => json::tokenize(line, state),
+            Language::Shell => shell::tokenize(line, state),
+        }
+    }
+}
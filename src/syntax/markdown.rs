@@ -0,0 +1,127 @@
+use super::scanner::Scanner;
+use super::{State, TokenKind};
+use crate::types::Line;
+
+pub fn tokenize(line: &Line, state: State) -> (Vec<TokenKind>, State) {
+    let mut s = Scanner::new(line);
+    s.skip_whitespace();
+    let fence = s.starts_with("```") || s.starts_with("~~~");
+
+    if state == State::CodeFence {
+        s.pos = 0;
+        s.mark_rest(TokenKind::Code);
+        let state = if fence {
+            State::Normal
+        } else {
+            State::CodeFence
+        };
+        return (s.finish(), state);
+    }
+
+    if fence {
+        s.pos = 0;
+        s.mark_rest(TokenKind::Code);
+        return (s.finish(), State::CodeFence);
+    }
+
+    block_marker(&mut s);
+
+    while !s.done() {
+        inline(&mut s);
+    }
+
+    (s.finish(), State::Normal)
+}
+
+// Headings, quotes, list items and rules at the start of a line
+fn block_marker(s: &mut Scanner) {
+    let Some(c) = s.peek() else {
+        return;
+    };
+
+    let hashes = s.chars[s.pos..].iter().take_while(|c| **c == '#').count();
+    if (1..=6).contains(&hashes) && s.peek_at(hashes).is_none_or(|c| c == ' ') {
+        s.mark_rest(TokenKind::Heading);
+        return;
+    }
+
+    let rest: String = s.chars[s.pos..].iter().filter(|c| **c != ' ').collect();
+    if rest.len() >= 3 && ["-", "*", "_"].iter().any(|r| rest == r.repeat(rest.len())) {
+        s.mark_rest(TokenKind::Punctuation);
+        return;
+    }
+
+    if c == '>' {
+        s.mark_rest(TokenKind::Comment);
+        return;
+    }
+
+    if matches!(c, '-' | '*' | '+') && s.peek_at(1) == Some(' ') {
+        s.mark(1, TokenKind::Keyword);
+        return;
+    }
+
+    let digits = s.chars[s.pos..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit())
+        .count();
+    if digits > 0 && matches!(s.peek_at(digits), Some('.') | Some(')')) {
+        s.mark(digits + 1, TokenKind::Keyword);
+    }
+}
+
+fn inline(s: &mut Scanner) {
+    let c = s.peek().unwrap();
+
+    match c {
+        '\\' => s.mark(2, TokenKind::Escape),
+        '`' => {
+            let ticks = s.chars[s.pos..].iter().take_while(|c| **c == '`').count();
+            let delimiter = "`".repeat(ticks);
+            match find(s, ticks, &delimiter) {
+                Some(end) => s.mark(end + ticks, TokenKind::Code),
+                None => s.mark(ticks, TokenKind::Text),
+            }
+        }
+        '*' | '_' => {
+            let run = s.chars[s.pos..].iter().take_while(|r| **r == c).count();
+            let delimiter = c.to_string().repeat(run);
+            let opens = s.peek_at(run).is_some_and(|n| !n.is_whitespace());
+            let intraword = c == '_' && s.previous().is_some_and(char::is_alphanumeric);
+
+            match find(s, run, &delimiter) {
+                Some(end) if opens && !intraword && end > run => {
+                    s.mark(end + run, TokenKind::Emphasis)
+                }
+                _ => s.mark(run, TokenKind::Text),
+            }
+        }
+        '[' => {
+            let Some(text_end) = find(s, 1, "]") else {
+                s.mark(1, TokenKind::Text);
+                return;
+            };
+
+            let len = if s.peek_at(text_end + 1) == Some('(') {
+                s.pos += text_end + 1;
+                let url_end = find(s, 1, ")");
+                s.pos -= text_end + 1;
+                url_end.map_or(text_end + 1, |end| text_end + 2 + end)
+            } else {
+                text_end + 1
+            };
+            s.mark(len, TokenKind::Link);
+        }
+        _ => s.mark(1, TokenKind::Text),
+    }
+}
+
+// Offset of `delimiter` from the current position, searching from `from`
+fn find(s: &Scanner, from: usize, delimiter: &str) -> Option<usize> {
+    let pattern: Vec<char> = delimiter.chars().collect();
+    let rest = s.chars.get(s.pos + from..)?;
+
+    rest.windows(pattern.len())
+        .position(|w| w == pattern.as_slice())
+        .map(|p| p + from)
+}
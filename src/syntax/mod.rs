@@ -0,0 +1,102 @@
+pub mod highlighter;
+pub mod json;
+pub mod language;
+pub mod markdown;
+pub mod rust;
+pub mod scanner;
+pub mod shell;
+pub mod toml;
+
+pub use highlighter::Highlighter;
+pub use language::Language;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TokenKind {
+    #[default]
+    Text,
+    Keyword,
+    Type,
+    Constant,
+    Number,
+    String,
+    Escape,
+    Comment,
+    Function,
+    Macro,
+    Attribute,
+    Property,
+    Variable,
+    Punctuation,
+    Heading,
+    Emphasis,
+    Link,
+    Code,
+}
+
+impl TokenKind {
+    pub const ALL: [TokenKind; 18] = [
+        TokenKind::Text,
+        TokenKind::Keyword,
+        TokenKind::Type,
+        TokenKind::Constant,
+        TokenKind::Number,
+        TokenKind::String,
+        TokenKind::Escape,
+        TokenKind::Comment,
+        TokenKind::Function,
+        TokenKind::Macro,
+        TokenKind::Attribute,
+        TokenKind::Property,
+        TokenKind::Variable,
+        TokenKind::Punctuation,
+        TokenKind::Heading,
+        TokenKind::Emphasis,
+        TokenKind::Link,
+        TokenKind::Code,
+    ];
+
+    // Name used for the token class in theme files
+    pub fn name(&self) -> &'static str {
+        match self {
+            TokenKind::Text => "text",
+            TokenKind::Keyword => "keyword",
+            TokenKind::Type => "type",
+            TokenKind::Constant => "constant",
+            TokenKind::Number => "number",
+            TokenKind::String => "string",
+            TokenKind::Escape => "escape",
+            TokenKind::Comment => "comment",
+            TokenKind::Function => "function",
+            TokenKind::Macro => "macro",
+            TokenKind::Attribute => "attribute",
+            TokenKind::Property => "property",
+            TokenKind::Variable => "variable",
+            TokenKind::Punctuation => "punctuation",
+            TokenKind::Heading => "heading",
+            TokenKind::Emphasis => "emphasis",
+            TokenKind::Link => "link",
+            TokenKind::Code => "code",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+// Tokenizer state carried from the end of one line to the start of the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum State {
+    #[default]
+    Normal,
+    // Nesting depth of an unterminated block comment
+    BlockComment(u8),
+    // Unterminated string and its closing quote
+    String(char),
+    // Unterminated raw string and the number of `#`s closing it
+    RawString(u8),
+    // Unterminated triple-quoted string
+    MultilineString(char),
+    // Inside a fenced code block
+    CodeFence,
+}
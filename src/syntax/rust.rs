@@ -0,0 +1,186 @@
+use super::scanner::Scanner;
+use super::{State, TokenKind};
+use crate::types::Line;
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "type", "unsafe", "use",
+    "where", "while", "yield",
+];
+
+const PRIMITIVES: &[&str] = &[
+    "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+    "i128", "isize", "f32", "f64",
+];
+
+pub fn tokenize(line: &Line, state: State) -> (Vec<TokenKind>, State) {
+    let mut s = Scanner::new(line);
+    let mut state = state;
+
+    while !s.done() {
+        state = match state {
+            State::BlockComment(depth) => block_comment(&mut s, depth),
+            State::String(quote) => string(&mut s, quote),
+            State::RawString(hashes) => raw_string(&mut s, hashes),
+            _ => token(&mut s),
+        };
+    }
+
+    (s.finish(), state)
+}
+
+fn token(s: &mut Scanner) -> State {
+    let c = s.peek().unwrap();
+
+    if s.starts_with("//") {
+        s.mark_rest(TokenKind::Comment);
+    } else if s.starts_with("/*") {
+        s.mark(2, TokenKind::Comment);
+        return block_comment(s, 1);
+    } else if c == '"' {
+        s.mark(1, TokenKind::String);
+        return string(s, '"');
+    } else if let Some(hashes) = raw_string_prefix(s) {
+        return raw_string(s, hashes);
+    } else if c == 'b' && matches!(s.peek_at(1), Some('"') | Some('\'')) {
+        // Byte string or byte literal, the quote is handled next
+        s.mark(1, TokenKind::String);
+    } else if c == '\'' {
+        char_or_lifetime(s);
+    } else if c == '#' && (s.starts_with("#[") || s.starts_with("#![")) {
+        attribute(s);
+    } else if c.is_ascii_digit() {
+        s.mark(s.number_len(), TokenKind::Number);
+    } else if c.is_alphabetic() || c == '_' {
+        word(s);
+    } else if c.is_ascii_punctuation() {
+        s.mark(1, TokenKind::Punctuation);
+    } else {
+        s.mark(1, TokenKind::Text);
+    }
+
+    State::Normal
+}
+
+fn word(s: &mut Scanner) {
+    let len = s.word_len();
+    let word = s.word(len);
+    let next = s.peek_at(len);
+
+    if KEYWORDS.contains(&word.as_str()) {
+        s.mark(len, TokenKind::Keyword);
+    } else if next == Some('!') && s.peek_at(len + 1) != Some('=') {
+        s.mark(len + 1, TokenKind::Macro);
+    } else if word == "true"
+        || word == "false"
+        || len > 1 && word.chars().all(|c| !c.is_lowercase())
+    {
+        s.mark(len, TokenKind::Constant);
+    } else if PRIMITIVES.contains(&word.as_str()) || word.starts_with(char::is_uppercase) {
+        s.mark(len, TokenKind::Type);
+    } else if next == Some('(') {
+        s.mark(len, TokenKind::Function);
+    } else {
+        s.mark(len, TokenKind::Text);
+    }
+}
+
+fn string(s: &mut Scanner, quote: char) -> State {
+    if s.string_body(quote, true) {
+        State::Normal
+    } else {
+        State::String(quote)
+    }
+}
+
+// Detects `r"`, `r#"`, `br##"` etc. and consumes the opening delimiter
+fn raw_string_prefix(s: &mut Scanner) -> Option<u8> {
+    let prefix = match (s.peek(), s.peek_at(1)) {
+        (Some('r'), _) => 1,
+        (Some('b'), Some('r')) => 2,
+        _ => return None,
+    };
+
+    let hashes = s.chars[s.pos + prefix..]
+        .iter()
+        .take_while(|c| **c == '#')
+        .count();
+
+    if s.peek_at(prefix + hashes) != Some('"') {
+        return None;
+    }
+
+    s.mark(prefix + hashes + 1, TokenKind::String);
+    Some(hashes as u8)
+}
+
+fn raw_string(s: &mut Scanner, hashes: u8) -> State {
+    while let Some(c) = s.peek() {
+        if c == '"' && (1..=hashes as usize).all(|i| s.peek_at(i) == Some('#')) {
+            s.mark(1 + hashes as usize, TokenKind::String);
+            return State::Normal;
+        }
+        s.mark(1, TokenKind::String);
+    }
+
+    State::RawString(hashes)
+}
+
+fn block_comment(s: &mut Scanner, mut depth: u8) -> State {
+    while !s.done() {
+        if s.starts_with("*/") {
+            s.mark(2, TokenKind::Comment);
+            depth -= 1;
+            if depth == 0 {
+                return State::Normal;
+            }
+        } else if s.starts_with("/*") {
+            s.mark(2, TokenKind::Comment);
+            depth = depth.saturating_add(1);
+        } else {
+            s.mark(1, TokenKind::Comment);
+        }
+    }
+
+    State::BlockComment(depth)
+}
+
+fn char_or_lifetime(s: &mut Scanner) {
+    if s.peek_at(1) == Some('\\') {
+        // Escaped char literal such as '\n' or '\u{1F600}'
+        let len = s.chars[s.pos + 2..]
+            .iter()
+            .position(|c| *c == '\'')
+            .map_or(2, |p| p + 3);
+        s.mark(len, TokenKind::String);
+    } else if s.peek_at(2) == Some('\'') {
+        s.mark(3, TokenKind::String);
+    } else {
+        s.pos += 1;
+        let len = s.word_len();
+        s.pos -= 1;
+        s.mark(len + 1, TokenKind::Type);
+    }
+}
+
+fn attribute(s: &mut Scanner) {
+    let mut depth = 0;
+    let mut len = 0;
+
+    while let Some(c) = s.peek_at(len) {
+        len += 1;
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    s.mark(len, TokenKind::Attribute);
+}
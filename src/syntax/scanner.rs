@@ -0,0 +1,120 @@
+use super::TokenKind;
+use crate::types::Line;
+
+// Walks a line one grapheme at a time, recording a token kind for each.
+// Graphemes are reduced to their first char, which is all the tokenizers
+// need since every piece of syntax they look for is ASCII.
+pub struct Scanner {
+    pub chars: Vec<char>,
+    pub pos: usize,
+    pub tokens: Vec<TokenKind>,
+}
+
+impl Scanner {
+    pub fn new(line: &Line) -> Self {
+        let chars: Vec<char> = line
+            .graphemes
+            .iter()
+            .map(|g| g.chars().next().unwrap_or(' '))
+            .collect();
+        let tokens = vec![TokenKind::Text; chars.len()];
+
+        Self {
+            chars,
+            pos: 0,
+            tokens,
+        }
+    }
+
+    pub fn done(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    pub fn peek_at(&self, n: usize) -> Option<char> {
+        self.chars.get(self.pos + n).copied()
+    }
+
+    pub fn previous(&self) -> Option<char> {
+        self.pos
+            .checked_sub(1)
+            .and_then(|i| self.chars.get(i).copied())
+    }
+
+    pub fn starts_with(&self, pattern: &str) -> bool {
+        pattern
+            .chars()
+            .enumerate()
+            .all(|(i, c)| self.peek_at(i) == Some(c))
+    }
+
+    // Mark the next `len` graphemes as `kind` and advance past them
+    pub fn mark(&mut self, len: usize, kind: TokenKind) {
+        let end = (self.pos + len).min(self.chars.len());
+        for token in &mut self.tokens[self.pos..end] {
+            *token = kind;
+        }
+        self.pos = end;
+    }
+
+    pub fn mark_rest(&mut self, kind: TokenKind) {
+        self.mark(self.chars.len() - self.pos, kind);
+    }
+
+    // Length of the identifier starting at the current position
+    pub fn word_len(&self) -> usize {
+        self.chars[self.pos..]
+            .iter()
+            .take_while(|c| c.is_alphanumeric() || **c == '_')
+            .count()
+    }
+
+    pub fn word(&self, len: usize) -> String {
+        self.chars[self.pos..self.pos + len].iter().collect()
+    }
+
+    // Length of a numeric literal starting at the current position. Accepts
+    // digits, letters (for hex, exponents and suffixes), `_` and `.` when it
+    // is followed by a digit.
+    pub fn number_len(&self) -> usize {
+        let mut len = 0;
+        while let Some(c) = self.peek_at(len) {
+            let fraction = c == '.' && self.peek_at(len + 1).is_some_and(|c| c.is_ascii_digit());
+            if c.is_ascii_alphanumeric() || c == '_' || fraction {
+                len += 1;
+            } else {
+                break;
+            }
+        }
+        len
+    }
+
+    // Consume a quoted string body up to and including `quote`, honouring
+    // backslash escapes. Returns true if the closing quote was found.
+    pub fn string_body(&mut self, quote: char, escapes: bool) -> bool {
+        while let Some(c) = self.peek() {
+            if escapes && c == '\\' {
+                self.mark(2, TokenKind::Escape);
+            } else if c == quote {
+                self.mark(1, TokenKind::String);
+                return true;
+            } else {
+                self.mark(1, TokenKind::String);
+            }
+        }
+        false
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    pub fn finish(self) -> Vec<TokenKind> {
+        self.tokens
+    }
+}
@@ -0,0 +1,102 @@
+use super::scanner::Scanner;
+use super::{State, TokenKind};
+use crate::types::Line;
+
+const KEYWORDS: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+    "in", "function", "select", "time", "return", "local", "export", "declare", "readonly",
+    "unset", "exit", "break", "continue", "source", "alias", "shift", "trap", "eval", "exec",
+];
+
+pub fn tokenize(line: &Line, state: State) -> (Vec<TokenKind>, State) {
+    let mut s = Scanner::new(line);
+    let mut state = state;
+
+    while !s.done() {
+        state = match state {
+            State::String(quote) => string(&mut s, quote),
+            _ => token(&mut s),
+        };
+    }
+
+    (s.finish(), state)
+}
+
+fn token(s: &mut Scanner) -> State {
+    let c = s.peek().unwrap();
+    // `#` only starts a comment at the beginning of a word
+    let word_start = s
+        .previous()
+        .is_none_or(|p| p.is_whitespace() || ";|&(".contains(p));
+
+    if c == '#' && word_start {
+        s.mark_rest(TokenKind::Comment);
+    } else if c == '"' || c == '\'' {
+        s.mark(1, TokenKind::String);
+        return string(s, c);
+    } else if c == '\\' {
+        s.mark(2, TokenKind::Escape);
+    } else if c == '$' {
+        variable(s);
+    } else if c.is_ascii_digit() && word_start {
+        s.mark(s.number_len(), TokenKind::Number);
+    } else if (c.is_alphabetic() || c == '_') && word_start {
+        let len = s.word_len();
+        let word = s.word(len);
+
+        let kind = if KEYWORDS.contains(&word.as_str()) {
+            TokenKind::Keyword
+        } else if s.peek_at(len) == Some('(') && s.peek_at(len + 1) == Some(')') {
+            TokenKind::Function
+        } else {
+            TokenKind::Text
+        };
+        s.mark(len, kind);
+    } else if "|&;<>()[]{}=".contains(c) {
+        s.mark(1, TokenKind::Punctuation);
+    } else {
+        s.mark(1, TokenKind::Text);
+    }
+
+    State::Normal
+}
+
+fn string(s: &mut Scanner, quote: char) -> State {
+    while let Some(c) = s.peek() {
+        if quote == '"' && c == '\\' {
+            s.mark(2, TokenKind::Escape);
+        } else if quote == '"' && c == '$' {
+            variable(s);
+        } else if c == quote {
+            s.mark(1, TokenKind::String);
+            return State::Normal;
+        } else {
+            s.mark(1, TokenKind::String);
+        }
+    }
+
+    State::String(quote)
+}
+
+// `$NAME`, `${...}`, `$1`, `$@` and friends. `$(` is left to the caller.
+fn variable(s: &mut Scanner) {
+    let len = match s.peek_at(1) {
+        Some('{') => s.chars[s.pos..]
+            .iter()
+            .position(|c| *c == '}')
+            .map_or(2, |p| p + 1),
+        Some(c) if c.is_alphabetic() || c == '_' => {
+            s.pos += 1;
+            let len = s.word_len();
+            s.pos -= 1;
+            len + 1
+        }
+        Some(c) if c.is_ascii_digit() || "@*#?$!-".contains(c) => 2,
+        _ => {
+            s.mark(1, TokenKind::Punctuation);
+            return;
+        }
+    };
+
+    s.mark(len, TokenKind::Variable);
+}
@@ -0,0 +1,120 @@
+use super::scanner::Scanner;
+use super::{State, TokenKind};
+use crate::types::Line;
+
+pub fn tokenize(line: &Line, state: State) -> (Vec<TokenKind>, State) {
+    let mut s = Scanner::new(line);
+    let mut state = state;
+
+    if state == State::Normal {
+        s.skip_whitespace();
+        if s.peek() == Some('[') {
+            table_header(&mut s);
+        }
+    }
+
+    while !s.done() {
+        state = match state {
+            State::MultilineString(quote) => multiline_string(&mut s, quote),
+            _ => token(&mut s),
+        };
+    }
+
+    (s.finish(), state)
+}
+
+fn token(s: &mut Scanner) -> State {
+    let c = s.peek().unwrap();
+
+    if c == '#' {
+        s.mark_rest(TokenKind::Comment);
+    } else if c == '"' || c == '\'' {
+        if s.starts_with(&c.to_string().repeat(3)) {
+            s.mark(3, TokenKind::String);
+            return multiline_string(s, c);
+        }
+
+        let start = s.pos;
+        s.mark(1, TokenKind::String);
+        s.string_body(c, c == '"');
+        if is_key(s) {
+            let end = s.pos;
+            s.pos = start;
+            s.mark(end - start, TokenKind::Property);
+        }
+    } else if c.is_alphanumeric() || c == '_' || c == '-' || c == '+' {
+        bare(s);
+    } else if c.is_ascii_punctuation() {
+        s.mark(1, TokenKind::Punctuation);
+    } else {
+        s.mark(1, TokenKind::Text);
+    }
+
+    State::Normal
+}
+
+// Bare keys, numbers, dates and booleans
+fn bare(s: &mut Scanner) {
+    let start = s.pos;
+    let len = s.chars[s.pos..]
+        .iter()
+        .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '+' | ':' | '.'))
+        .count();
+    let word = s.word(len);
+
+    // Dotted keys like `a.b.c = 1` are split on the dots
+    s.pos += len;
+    let key = is_key(s);
+    s.pos = start;
+
+    let kind = if key {
+        TokenKind::Property
+    } else if matches!(
+        word.as_str(),
+        "true" | "false" | "inf" | "nan" | "+inf" | "-inf"
+    ) {
+        TokenKind::Constant
+    } else if word
+        .trim_start_matches(['+', '-'])
+        .starts_with(|c: char| c.is_ascii_digit())
+    {
+        TokenKind::Number
+    } else {
+        TokenKind::Text
+    };
+
+    s.mark(len, kind);
+}
+
+// A key is followed by `=`, possibly with whitespace in between
+fn is_key(s: &Scanner) -> bool {
+    s.chars[s.pos..]
+        .iter()
+        .find(|c| !c.is_whitespace())
+        .is_some_and(|c| *c == '=' || *c == '.')
+}
+
+fn table_header(s: &mut Scanner) {
+    let len = s.chars[s.pos..]
+        .iter()
+        .rposition(|c| *c == ']')
+        .map_or(s.chars.len() - s.pos, |p| p + 1);
+    s.mark(len, TokenKind::Type);
+}
+
+fn multiline_string(s: &mut Scanner, quote: char) -> State {
+    let delimiter = quote.to_string().repeat(3);
+
+    while let Some(c) = s.peek() {
+        if quote == '"' && c == '\\' {
+            s.mark(2, TokenKind::Escape);
+        } else if s.starts_with(&delimiter) {
+            s.mark(3, TokenKind::String);
+            return State::Normal;
+        } else {
+            s.mark(1, TokenKind::String);
+        }
+    }
+
+    State::MultilineString(quote)
+}
@@ -0,0 +1,158 @@
+use super::color::{ColorSupport, parse_color};
+use super::theme::{Style, Theme};
+use crate::config::config_dir;
+use crate::syntax::TokenKind;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+const BUILTIN_THEMES: &[(&str, &str)] = &[
+    ("default", include_str!("themes/default.toml")),
+    ("one-dark", include_str!("themes/one-dark.toml")),
+    ("gruvbox-dark", include_str!("themes/gruvbox-dark.toml")),
+    (
+        "solarized-light",
+        include_str!("themes/solarized-light.toml"),
+    ),
+    ("github-light", include_str!("themes/github-light.toml")),
+];
+
+// Themes may inherit from each other, guard against cycles
+const MAX_INHERITANCE_DEPTH: usize = 8;
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    inherits: Option<String>,
+    ui: UiSection,
+    syntax: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct UiSection {
+    text: Option<StyleSpec>,
+    selection: Option<StyleSpec>,
+    status_bar: Option<StyleSpec>,
+    gutter: Option<StyleSpec>,
+    search_match: Option<StyleSpec>,
+    whitespace: Option<StyleSpec>,
+    trailing_whitespace: Option<StyleSpec>,
+    control: Option<StyleSpec>,
+    matching_bracket: Option<StyleSpec>,
+    secondary_cursor: Option<StyleSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StyleSpec {
+    fg: Option<String>,
+    bg: Option<String>,
+}
+
+impl StyleSpec {
+    fn to_style(&self) -> Result<Style, String> {
+        Ok(Style {
+            fg: self.fg.as_deref().map(parse_color).transpose()?,
+            bg: self.bg.as_deref().map(parse_color).transpose()?,
+        })
+    }
+}
+
+fn user_themes_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("themes"))
+}
+
+impl Theme {
+    // Load a theme by name, preferring user themes over built-in ones, and
+    // convert its colors for the current terminal
+    pub fn load(name: &str) -> Result<Theme, String> {
+        let mut theme = Self::load_named(name, 0)?;
+        theme.downsample(ColorSupport::detect());
+        Ok(theme)
+    }
+
+    // Names of all built-in and user themes
+    pub fn available() -> Vec<String> {
+        let mut names: Vec<String> = BUILTIN_THEMES
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        if let Some(Ok(entries)) = user_themes_dir().map(fs::read_dir) {
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.extension().is_some_and(|e| e == "toml")
+                    && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+                    && !names.iter().any(|n| n == stem)
+                {
+                    names.push(stem.to_string());
+                }
+            }
+        }
+
+        names
+    }
+
+    fn load_named(name: &str, depth: usize) -> Result<Theme, String> {
+        if depth > MAX_INHERITANCE_DEPTH {
+            return Err(format!("theme '{}' inherits too deeply", name));
+        }
+
+        let user_file = user_themes_dir().map(|dir| dir.join(format!("{}.toml", name)));
+        let source = match user_file.and_then(|path| fs::read_to_string(path).ok()) {
+            Some(source) => source,
+            None => BUILTIN_THEMES
+                .iter()
+                .find(|(builtin, _)| *builtin == name)
+                .map(|(_, source)| source.to_string())
+                .ok_or_else(|| format!("unknown theme '{}'", name))?,
+        };
+
+        let file: ThemeFile =
+            toml::from_str(&source).map_err(|e| format!("theme '{}': {}", name, e))?;
+
+        let mut theme = match (file.inherits.as_deref(), name) {
+            (Some(parent), _) => Self::load_named(parent, depth + 1)?,
+            (None, "default") => Theme::default(),
+            (None, _) => Self::load_named("default", depth + 1)?,
+        };
+
+        theme.name = name.to_string();
+        file.apply(&mut theme)
+            .map_err(|e| format!("theme '{}': {}", name, e))?;
+
+        Ok(theme)
+    }
+}
+
+impl ThemeFile {
+    fn apply(&self, theme: &mut Theme) -> Result<(), String> {
+        let ui = [
+            (&self.ui.text, &mut theme.text),
+            (&self.ui.selection, &mut theme.selection),
+            (&self.ui.status_bar, &mut theme.status_bar),
+            (&self.ui.gutter, &mut theme.gutter),
+            (&self.ui.search_match, &mut theme.search_match),
+            (&self.ui.whitespace, &mut theme.whitespace),
+            (&self.ui.trailing_whitespace, &mut theme.trailing_whitespace),
+            (&self.ui.control, &mut theme.control),
+            (&self.ui.matching_bracket, &mut theme.matching_bracket),
+            (&self.ui.secondary_cursor, &mut theme.secondary_cursor),
+        ];
+
+        for (spec, style) in ui {
+            if let Some(spec) = spec {
+                *style = style.patch(spec.to_style()?);
+            }
+        }
+
+        for (name, color) in &self.syntax {
+            let kind = TokenKind::from_name(name)
+                .ok_or_else(|| format!("unknown token class '{}'", name))?;
+            theme.syntax.insert(kind, parse_color(color)?);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+pub mod color;
+pub mod loader;
+#[allow(clippy::module_inception)]
+pub mod theme;
+
+pub use color::ColorSupport;
+pub use theme::{Style, Theme};
@@ -0,0 +1,79 @@
+use super::color::ColorSupport;
+use crate::syntax::TokenKind;
+use crossterm::style::{Color, ContentStyle, StyledContent};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl Style {
+    pub fn apply<D: Display>(&self, content: D) -> StyledContent<D> {
+        let style = ContentStyle {
+            foreground_color: self.fg,
+            background_color: self.bg,
+            ..ContentStyle::default()
+        };
+        StyledContent::new(style, content)
+    }
+
+    // Layer `other` on top of this style, keeping our colors where it has none
+    pub fn patch(&self, other: Style) -> Style {
+        Style {
+            fg: other.fg.or(self.fg),
+            bg: other.bg.or(self.bg),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Theme {
+    pub name: String,
+    pub text: Style,
+    pub selection: Style,
+    pub status_bar: Style,
+    pub gutter: Style,
+    pub search_match: Style,
+    pub whitespace: Style,
+    pub trailing_whitespace: Style,
+    pub control: Style,
+    pub matching_bracket: Style,
+    pub secondary_cursor: Style,
+    pub syntax: HashMap<TokenKind, Color>,
+}
+
+impl Theme {
+    // Style for a grapheme of the given token class on top of the base text style
+    pub fn token(&self, kind: TokenKind) -> Style {
+        Style {
+            fg: self.syntax.get(&kind).copied().or(self.text.fg),
+            bg: self.text.bg,
+        }
+    }
+
+    // Convert every color to something the terminal can display
+    pub fn downsample(&mut self, support: ColorSupport) {
+        for style in [
+            &mut self.text,
+            &mut self.selection,
+            &mut self.status_bar,
+            &mut self.gutter,
+            &mut self.search_match,
+            &mut self.whitespace,
+            &mut self.trailing_whitespace,
+            &mut self.control,
+            &mut self.matching_bracket,
+            &mut self.secondary_cursor,
+        ] {
+            style.fg = style.fg.map(|c| support.convert(c));
+            style.bg = style.bg.map(|c| support.convert(c));
+        }
+
+        for color in self.syntax.values_mut() {
+            *color = support.convert(*color);
+        }
+    }
+}
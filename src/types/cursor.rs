@@ -0,0 +1,9 @@
+use super::Point;
+
+// An additional cursor when editing in several places at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub position: Point,
+    pub selection: Option<[Point; 2]>,
+    pub preferred_width: usize,
+}
@@ -0,0 +1,230 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+    Cr,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::Crlf => "CRLF",
+            LineEnding::Cr => "CR",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "lf" => Some(LineEnding::Lf),
+            "crlf" => Some(LineEnding::Crlf),
+            "cr" => Some(LineEnding::Cr),
+            _ => None,
+        }
+    }
+
+    // The ending most lines use. Files with mixed endings are written back
+    // with this one.
+    fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        if crlf > lf {
+            LineEnding::Crlf
+        } else if lf == 0 && text.contains('\r') {
+            LineEnding::Cr
+        } else {
+            LineEnding::Lf
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Charset {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Latin1,
+    Utf16Be,
+    Utf16Le,
+}
+
+impl Charset {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Charset::Utf8 => "UTF-8",
+            Charset::Utf8Bom => "UTF-8 BOM",
+            Charset::Latin1 => "Latin-1",
+            Charset::Utf16Be => "UTF-16 BE",
+            Charset::Utf16Le => "UTF-16 LE",
+        }
+    }
+
+    // Names as used by .editorconfig
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "utf-8" => Some(Charset::Utf8),
+            "utf-8-bom" => Some(Charset::Utf8Bom),
+            "latin1" => Some(Charset::Latin1),
+            "utf-16be" => Some(Charset::Utf16Be),
+            "utf-16le" => Some(Charset::Utf16Le),
+            _ => None,
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Option<String> {
+        match self {
+            Charset::Utf8 | Charset::Utf8Bom => {
+                let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+                String::from_utf8(bytes.to_vec()).ok()
+            }
+            Charset::Latin1 => Some(bytes.iter().map(|b| *b as char).collect()),
+            Charset::Utf16Be | Charset::Utf16Le => {
+                let bom: &[u8] = if *self == Charset::Utf16Be {
+                    b"\xFE\xFF"
+                } else {
+                    b"\xFF\xFE"
+                };
+                let bytes = bytes.strip_prefix(bom).unwrap_or(bytes);
+                if !bytes.len().is_multiple_of(2) {
+                    return None;
+                }
+
+                let units = bytes.chunks(2).map(|pair| match self {
+                    Charset::Utf16Be => u16::from_be_bytes([pair[0], pair[1]]),
+                    _ => u16::from_le_bytes([pair[0], pair[1]]),
+                });
+                char::decode_utf16(units)
+                    .collect::<Result<String, _>>()
+                    .ok()
+            }
+        }
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>, String> {
+        match self {
+            Charset::Utf8 => Ok(text.as_bytes().to_vec()),
+            Charset::Utf8Bom => Ok([b"\xEF\xBB\xBF", text.as_bytes()].concat()),
+            Charset::Latin1 => text
+                .chars()
+                .map(|c| {
+                    u8::try_from(c as u32)
+                        .map_err(|_| format!("'{}' can't be encoded as Latin-1", c))
+                })
+                .collect(),
+            Charset::Utf16Be => Ok(text.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+            Charset::Utf16Le => Ok(text.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+        }
+    }
+}
+
+// How a file is stored on disk, so it can be written back the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileFormat {
+    pub line_ending: LineEnding,
+    pub charset: Charset,
+    pub final_newline: bool,
+}
+
+impl Default for FileFormat {
+    fn default() -> Self {
+        Self {
+            line_ending: LineEnding::Lf,
+            charset: Charset::Utf8,
+            final_newline: true,
+        }
+    }
+}
+
+impl FileFormat {
+    // Decode a file and split it into lines. `charset` is used if given,
+    // otherwise it's detected from a byte order mark, falling back to Latin-1
+    // for files that aren't valid UTF-8.
+    pub fn decode(bytes: &[u8], charset: Option<Charset>) -> (Vec<String>, FileFormat) {
+        let detected = if bytes.starts_with(b"\xEF\xBB\xBF") {
+            Charset::Utf8Bom
+        } else if bytes.starts_with(b"\xFE\xFF") {
+            Charset::Utf16Be
+        } else if bytes.starts_with(b"\xFF\xFE") {
+            Charset::Utf16Le
+        } else if std::str::from_utf8(bytes).is_ok() {
+            Charset::Utf8
+        } else {
+            Charset::Latin1
+        };
+
+        let (text, charset) = charset
+            .and_then(|charset| Some((charset.decode(bytes)?, charset)))
+            .or_else(|| Some((detected.decode(bytes)?, detected)))
+            .unwrap_or_else(|| (Charset::Latin1.decode(bytes).unwrap(), Charset::Latin1));
+
+        let line_ending = LineEnding::detect(&text);
+        // Split on any line feed so that mixed endings don't merge lines
+        let separator = match line_ending {
+            LineEnding::Cr => '\r',
+            _ => '\n',
+        };
+        let final_newline = text.ends_with(separator);
+        let body = text.strip_suffix(separator).unwrap_or(&text);
+        let lines = body
+            .split(separator)
+            .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string())
+            .collect();
+
+        let format = FileFormat {
+            line_ending,
+            charset,
+            // Keep new and empty files ending with a newline
+            final_newline: final_newline || text.is_empty(),
+        };
+        (lines, format)
+    }
+
+    pub fn encode(&self, lines: &[String]) -> Result<Vec<u8>, String> {
+        let mut text = lines.join(self.line_ending.as_str());
+        if self.final_newline {
+            text.push_str(self.line_ending.as_str());
+        }
+        self.charset.encode(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_mixed_line_endings() {
+        let (lines, format) = FileFormat::decode(b"one\r\ntwo\nthree\r\nfour\r\n", None);
+        assert_eq!(lines, ["one", "two", "three", "four"]);
+        assert_eq!(format.line_ending, LineEnding::Crlf);
+        assert!(format.final_newline);
+
+        let bytes = format.encode(&lines).unwrap();
+        assert_eq!(bytes, b"one\r\ntwo\r\nthree\r\nfour\r\n");
+    }
+
+    #[test]
+    fn decode_mostly_lf() {
+        let (lines, format) = FileFormat::decode(b"one\ntwo\r\nthree", None);
+        assert_eq!(lines, ["one", "two", "three"]);
+        assert_eq!(format.line_ending, LineEnding::Lf);
+        assert!(!format.final_newline);
+    }
+
+    #[test]
+    fn decode_cr() {
+        let (lines, format) = FileFormat::decode(b"one\rtwo\r", None);
+        assert_eq!(lines, ["one", "two"]);
+        assert_eq!(format.line_ending, LineEnding::Cr);
+        assert!(format.final_newline);
+    }
+}
@@ -1,10 +1,104 @@
+use crate::config::BufferSettings;
+use crate::syntax::TokenKind;
+use crate::theme::Theme;
+use std::ops::Range;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
-use crossterm::style::Stylize;
 
-const TAB_WIDTH: usize = 4;
+// Control characters are drawn in caret notation (`^[` for ESC, `^?` for DEL)
+// or as a hex code for C1 controls, so file contents can't send escape
+// sequences to the terminal. Returns None for graphemes that are safe to print.
+pub fn control_placeholder(grapheme: &str) -> Option<String> {
+    if !grapheme.chars().any(|c| c.is_control() && c != '\t') {
+        return None;
+    }
+
+    let placeholder = grapheme
+        .chars()
+        .map(|c| match c as u32 {
+            0x00..=0x1F => format!("^{}", (c as u8 + 0x40) as char),
+            0x7F => "^?".to_string(),
+            0x80..=0x9F => format!("<{:02x}>", c as u32),
+            _ => c.to_string(),
+        })
+        .collect();
+
+    Some(placeholder)
+}
+
+// Replace any control characters in text that isn't part of the buffer,
+// such as file names shown in the status line
+pub fn sanitize(text: &str) -> String {
+    text.graphemes(true)
+        .map(|g| control_placeholder(g).unwrap_or_else(|| g.to_string()))
+        .collect()
+}
+
+// Cut text down to at most `width` columns, ending in "…" if anything was
+// dropped. Never splits a grapheme.
+pub fn truncate_to_width(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let mut truncated = String::new();
+    let mut used = 0;
+    for grapheme in text.graphemes(true) {
+        let w = grapheme.width();
+        if used + w + 1 > width {
+            break;
+        }
+        truncated.push_str(grapheme);
+        used += w;
+    }
+    if width > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
+// Display width of a grapheme that starts at the given column
+fn grapheme_width(grapheme: &str, column: usize, tab_width: usize) -> usize {
+    if grapheme == "\t" {
+        tab_width - (column % tab_width)
+    } else if let Some(placeholder) = control_placeholder(grapheme) {
+        placeholder.width()
+    } else {
+        grapheme.width()
+    }
+}
+
+fn is_blank(grapheme: &str) -> bool {
+    grapheme == " " || grapheme == "\t"
+}
+
+// Split text into lines on any kind of line break
+pub fn lines_from_text(text: &str) -> Vec<Line> {
+    text.replace("\r\n", "\n")
+        .split(['\n', '\r'])
+        .map(|line| Line::from_string(line.to_string()))
+        .collect()
+}
+
+pub fn lines_to_text(lines: &[Line]) -> String {
+    lines
+        .iter()
+        .map(|line| line.graphemes.concat())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Grapheme indices on a line that are drawn highlighted
+#[derive(Debug, Clone, Default)]
+pub struct Marks {
+    // Selected ranges, inclusive
+    pub selections: Vec<[usize; 2]>,
+    pub brackets: Vec<usize>,
+    // Cursors other than the terminal's own
+    pub cursors: Vec<usize>,
+}
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct Line {
     pub graphemes: Vec<String>,
 }
@@ -24,58 +118,113 @@ impl Line {
         self.graphemes.len()
     }
 
-    pub fn width_to(&self, index: usize) -> usize {
+    pub fn is_empty(&self) -> bool {
+        self.graphemes.is_empty()
+    }
+
+    // Number of leading spaces and tabs
+    pub fn indent_len(&self) -> usize {
+        self.graphemes.iter().take_while(|g| is_blank(g)).count()
+    }
+
+    pub fn width_to(&self, index: usize, tab_width: usize) -> usize {
         let mut width = 0;
         for character in self.graphemes.iter().take(index) {
-            if character == "\t" {
-                // Calculate tab width based on current position
-                // Not sure how this works in tandem with emojis etc.
-                width += TAB_WIDTH - (width % TAB_WIDTH);
-            } else {
-                width += character.width();
-            }
+            width += grapheme_width(character, width, tab_width);
         }
         width
     }
 
-    pub fn x_at_width(&self, width_goal: usize) -> Option<usize> {
+    pub fn x_at_width(&self, width_goal: usize, tab_width: usize) -> Option<usize> {
         let mut width = 0;
-        let mut i = 0;
-        for character in &self.graphemes {
-            if character == "\t" {
-                width += TAB_WIDTH - (width % TAB_WIDTH);
-            } else {
-                width += character.width();
-            }
+        for (i, character) in self.graphemes.iter().enumerate() {
+            width += grapheme_width(character, width, tab_width);
 
             if width > width_goal {
                 return Some(i);
             }
-
-            i += 1;
         }
 
         None
     }
 
-    pub fn print(&self, offset: Option<usize>, highlight: Option<[usize; 2]>) {
-        let offset = offset.unwrap_or(0);
-        for (i, grapheme) in self.graphemes.iter().enumerate().skip(offset) {
-            let string = if grapheme == "\t" {
-                &" ".repeat(TAB_WIDTH)
-            } else {
-                grapheme
-            };
+    // Print the display columns in `columns`, which is the horizontal part
+    // of the line in view
+    pub fn print(
+        &self,
+        columns: Range<usize>,
+        marks: &Marks,
+        tokens: &[TokenKind],
+        theme: &Theme,
+        settings: &BufferSettings,
+    ) {
+        let indentation = self.graphemes.iter().take_while(|g| is_blank(g)).count();
+        let trailing = self.len()
+            - self
+                .graphemes
+                .iter()
+                .rev()
+                .take_while(|g| is_blank(g))
+                .count();
+        let mut column = 0;
 
-            if let Some([a, b]) = highlight {
-                if i >= a && i <= b {
-                    print!("{}", string.clone().on_blue())
-                } else {
-                    print!("{}", string)
+        for (i, grapheme) in self.graphemes.iter().enumerate() {
+            let width = grapheme_width(grapheme, column, settings.tab_width);
+            if column + width > columns.end {
+                break;
+            }
+            if column < columns.start {
+                // Keep the part of a tab or wide grapheme that's in view
+                let visible = (column + width).saturating_sub(columns.start);
+                print!("{}", " ".repeat(visible));
+                column += width;
+                continue;
+            }
+
+            let mut style = theme.token(tokens.get(i).copied().unwrap_or_default());
+            let placeholder = control_placeholder(grapheme);
+            let guide =
+                settings.indent_guides && i < indentation && column % settings.tab_width == 0;
+
+            let string = if is_blank(grapheme) {
+                // Tabs are padded to the next tab stop
+                let marker = match (guide, settings.show_whitespace, grapheme.as_str()) {
+                    (true, _, _) => "│",
+                    (false, true, "\t") => "→",
+                    (false, true, _) => "·",
+                    (false, false, _) => " ",
+                };
+                if guide || settings.show_whitespace {
+                    style = style.patch(theme.whitespace);
                 }
+                format!("{}{}", marker, " ".repeat(width - 1))
+            } else if let Some(placeholder) = placeholder {
+                style = style.patch(theme.control);
+                placeholder
             } else {
-                print!("{}", string)
+                grapheme.clone()
             };
+
+            if settings.show_whitespace && i >= trailing {
+                style = style.patch(theme.trailing_whitespace);
+            }
+            if marks.brackets.contains(&i) {
+                style = style.patch(theme.matching_bracket);
+            }
+            if marks.selections.iter().any(|[a, b]| (*a..=*b).contains(&i)) {
+                style = style.patch(theme.selection);
+            }
+            if marks.cursors.contains(&i) {
+                style = style.patch(theme.secondary_cursor);
+            }
+
+            print!("{}", style.apply(string));
+            column += width;
+        }
+
+        // A cursor past the end of the line still needs a cell to show it
+        if marks.cursors.contains(&self.len()) && columns.contains(&column) {
+            print!("{}", theme.secondary_cursor.apply(" "));
         }
     }
 }
@@ -1,7 +1,11 @@
+pub mod cursor;
+pub mod file_format;
 pub mod line;
 pub mod point;
 pub mod status;
 
-pub use line::Line;
+pub use cursor::Cursor;
+pub use file_format::{Charset, FileFormat, LineEnding};
+pub use line::{Line, Marks};
 pub use point::Point;
 pub use status::Status;
@@ -14,7 +14,7 @@ impl Status {
         }
     }
 
-    pub fn is_fresh(&self) -> bool {
-        self.timestamp.elapsed() < Duration::from_secs(3)
+    pub fn is_fresh(&self, timeout: Duration) -> bool {
+        self.timestamp.elapsed() < timeout
     }
 }